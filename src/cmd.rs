@@ -1,76 +1,299 @@
+mod capture;
 mod config;
-mod error;
+mod edit;
+pub mod error;
 mod executor;
+mod filter;
+mod flow;
+mod graphql;
+mod grpc;
+mod input;
+mod lint;
 pub mod parser;
+mod printer;
 mod resolver;
+mod run;
+mod scaffold;
+mod sse;
+mod stream;
+mod websocket;
 
-use std::{collections::HashMap, io::Write};
+use std::collections::HashMap;
 
 use self::{
-    config::{APIConfig, Config},
-    error::ExecutorError,
-    executor::{Engine, HttpResponse},
-    parser::{CmdArgs, Commands},
+    config::{APIConfig, APIEndpoint},
+    error::{ConfigError, ExecutorError},
+    parser::{
+        CmdArgs, Commands, ContextCommands, EndpointCommands, FlowCommands, GraphQLCommands,
+        NewCommands, RunArgs, SchemaKind,
+    },
 };
 
+fn validate_endpoint(
+    apis: &HashMap<String, APIConfig>,
+    api: &str,
+    endpoint: &str,
+) -> Result<(), ExecutorError> {
+    if !apis.contains_key(api) {
+        return Result::Err(ExecutorError::APINotFound(api.to_string()));
+    }
+    if !apis
+        .get(api)
+        .map(|api_config| api_config.contains_endpoint(endpoint))
+        .unwrap_or(false)
+    {
+        return Result::Err(ExecutorError::EndpointNotFound(endpoint.to_string()));
+    }
+    Result::Ok(())
+}
+
 fn validate(cmd_args: &CmdArgs, apis: &HashMap<String, APIConfig>) -> Result<(), ExecutorError> {
     match &cmd_args.command {
-        Commands::List => (),
-        Commands::Run {
-            api,
-            input: _,
-            context: _,
-            endpoint,
-            verbose: _,
+        Commands::Init { .. }
+        | Commands::New {
+            command: NewCommands::Api { .. },
+        }
+        | Commands::List
+        | Commands::Validate { .. }
+        | Commands::Schema { .. } => (),
+        Commands::New {
+            command: NewCommands::Endpoint { api, .. },
         } => {
             if !apis.contains_key(api) {
-                return Result::Err(ExecutorError::APINotFound(api.clone()));
+                return Result::Err(ExecutorError::APINotFound(api.to_string()));
+            }
+        }
+        Commands::Context { command } => {
+            let (api, context) = match command {
+                ContextCommands::Set { api, .. } => (api, None),
+                ContextCommands::Rm { api, context, .. } => (api, Some(context)),
+            };
+            if !apis.contains_key(api) {
+                return Result::Err(ExecutorError::APINotFound(api.to_string()));
+            }
+            if let Some(context) = context {
+                if !apis[api].contains_context(context) {
+                    return Result::Err(ExecutorError::ContextNotFound(context.clone()));
+                }
             }
-            if !apis
+        }
+        Commands::Endpoint { command } => match command {
+            EndpointCommands::Rm { api, name } => validate_endpoint(apis, api, name)?,
+            EndpointCommands::Mv {
+                api, name, to_api, ..
+            }
+            | EndpointCommands::Cp {
+                api, name, to_api, ..
+            } => {
+                validate_endpoint(apis, api, name)?;
+                if let Some(to_api) = to_api {
+                    if !apis.contains_key(to_api) {
+                        return Result::Err(ExecutorError::APINotFound(to_api.to_string()));
+                    }
+                }
+            }
+        },
+        Commands::Describe { api, endpoint } => validate_endpoint(apis, api, endpoint)?,
+        Commands::Run(RunArgs { api, endpoint, .. }) => validate_endpoint(apis, api, endpoint)?,
+        Commands::Graphql {
+            command:
+                GraphQLCommands::Introspect {
+                    api,
+                    context,
+                    endpoint,
+                    ..
+                },
+        } => {
+            validate_endpoint(apis, api, endpoint)?;
+            if let Some(context) = context {
+                if !apis[api].contains_context(context) {
+                    return Result::Err(ExecutorError::ContextNotFound(context.clone()));
+                }
+            }
+        }
+        Commands::Flow {
+            command: FlowCommands::Run {
+                api, flow, context, ..
+            },
+        } => {
+            let api_config = apis
                 .get(api)
-                .map(|api_config| api_config.contains_endpoint(endpoint))
-                .unwrap_or(false)
-            {
-                return Result::Err(ExecutorError::EndpointNotFound(endpoint.clone()));
+                .ok_or_else(|| ExecutorError::APINotFound(api.to_string()))?;
+            if api_config.get_flow(flow).is_none() {
+                return Result::Err(ExecutorError::FlowNotFound(flow.to_string()));
+            }
+            if let Some(context) = context {
+                if !api_config.contains_context(context) {
+                    return Result::Err(ExecutorError::ContextNotFound(context.clone()));
+                }
             }
         }
     }
     Result::Ok(())
 }
 
-fn printer(http_response: HttpResponse, verbose: &bool) -> Result<(), ExecutorError> {
-    if *verbose {
-        println!("{} {}", http_response.status, http_response.version);
-        for header in http_response.headers.iter() {
-            println!("{}: {}", header.key, header.value);
+fn sorted_keys<V>(map: &HashMap<String, V>) -> Vec<&String> {
+    let mut keys: Vec<&String> = map.keys().collect();
+    keys.sort();
+    keys
+}
+
+fn list(apis: &HashMap<String, APIConfig>) {
+    for api in sorted_keys(apis) {
+        println!("{}", api);
+        let endpoints = apis[api].get_api_endpoints();
+        for name in sorted_keys(endpoints) {
+            let endpoint = &endpoints[name];
+            println!("  {} {:?} {}", name, endpoint.method, endpoint.url);
+            if let Some(inputs) = &endpoint.inputs {
+                for input in sorted_keys(inputs) {
+                    println!("    -i {}", input::describe_input(input, &inputs[input]));
+                }
+            }
+        }
+        if let Some(flows) = apis[api].get_flows() {
+            for name in sorted_keys(flows) {
+                let flow = &flows[name];
+                match &flow.description {
+                    Some(description) => println!("  flow {}: {}", name, description),
+                    None => println!("  flow {}", name),
+                }
+            }
+        }
+    }
+}
+
+fn describe(api_config: &APIConfig, endpoint: &APIEndpoint) {
+    println!("{:?} {}", endpoint.method, endpoint.url);
+    let sources: Vec<String> = api_config
+        .get_sources()
+        .iter()
+        .map(|s| s.display().to_string())
+        .collect();
+    println!("defined in: {}", sources.join(", "));
+    if let Some(contexts) = api_config.get_api_contexts() {
+        let names: Vec<&str> = sorted_keys(contexts).iter().map(|c| c.as_str()).collect();
+        println!("contexts: {}", names.join(", "));
+    }
+    if let Some(headers) = &endpoint.headers {
+        println!("headers:");
+        for header in sorted_keys(headers) {
+            println!("  {}: {}", header, headers[header]);
         }
-        println!()
     }
-    if !http_response.body.is_empty() {
-        let mut out = std::io::stdout();
-        out.write_all(http_response.body.as_slice()).map_err(|err| ExecutorError::FailedToPrintBody(err.to_string()))?;
-        out.flush().map_err(|err| ExecutorError::FailedToPrintBody(err.to_string()))?;
+    match &endpoint.inputs {
+        Some(inputs) => {
+            println!("inputs:");
+            for input in sorted_keys(inputs) {
+                println!("  {}", input::describe_input(input, &inputs[input]));
+            }
+        }
+        None => println!("inputs: not declared, any -i key=value is accepted"),
     }
+}
+
+fn print_schema(kind: &SchemaKind) -> Result<(), ExecutorError> {
+    let schema = match kind {
+        SchemaKind::Config => config::config_schema(),
+        SchemaKind::Collection => config::collection_schema(),
+    };
+    let json = serde_json::to_string_pretty(&schema)
+        .map_err(|e| ExecutorError::FailedToPrintBody(e.to_string()))?;
+    println!("{}", json);
     Ok(())
 }
 
-pub fn execute(cmd_args: &CmdArgs, config: &Config) -> Result<(), ExecutorError> {
-    let apis = config.read_apis();
+pub fn execute(cmd_args: &CmdArgs) -> Result<(), ExecutorError> {
+    match &cmd_args.command {
+        Commands::Schema { kind } => return print_schema(kind),
+        Commands::Init { project } => return scaffold::init(&cmd_args.config_file()?, *project),
+        _ => (),
+    }
+    let config = cmd_args.read_config()?;
+    let (apis, errors) = config.read_apis();
+    if let Commands::Validate { format, strict } = &cmd_args.command {
+        return lint::report(
+            &lint::lint(&apis, &errors, &config.global_context()),
+            format,
+            *strict,
+        );
+    }
+    if !errors.is_empty() {
+        if !cmd_args.skip_invalid {
+            return Result::Err(ConfigError::Collections(errors).into());
+        }
+        for error in errors.iter() {
+            eprintln!("Skipping invalid collection: {}", error);
+        }
+    }
     validate(cmd_args, &apis)?;
     match &cmd_args.command {
-        Commands::List => print!("execute list"),
-        Commands::Run {
-            api,
-            context,
-            endpoint,
-            input,
-            verbose,
-        } => {
+        Commands::Context { command } => match command {
+            ContextCommands::Set {
+                api,
+                context,
+                values,
+            } => edit::set_context(api, &apis[api], context, values)?,
+            ContextCommands::Rm { api, context, keys } => {
+                edit::remove_context(api, &apis[api], context, keys)?
+            }
+        },
+        Commands::Endpoint { command } => match command {
+            EndpointCommands::Rm { api, name } => edit::remove_endpoint(api, &apis[api], name)?,
+            EndpointCommands::Mv {
+                api,
+                name,
+                new_name,
+                to_api,
+            } => edit::copy_endpoint(&apis, api, name, new_name, to_api, false)?,
+            EndpointCommands::Cp {
+                api,
+                name,
+                new_name,
+                to_api,
+            } => edit::copy_endpoint(&apis, api, name, new_name, to_api, true)?,
+        },
+        Commands::List => list(&apis),
+        Commands::Init { .. } | Commands::Validate { .. } | Commands::Schema { .. } => (),
+        Commands::New { command } => match command {
+            NewCommands::Api { name, file } => scaffold::new_api(&config, &apis, name, file)?,
+            NewCommands::Endpoint {
+                api,
+                name,
+                method,
+                url,
+                body,
+            } => scaffold::new_endpoint(api, &apis[api], name, method, url, body)?,
+        },
+        Commands::Describe { api, endpoint } => {
             let api_config = apis.get(api).unwrap();
-            let mut engine = Engine::new();
-            let result = engine.run(api_config, endpoint, context, input)?;
-            printer(result, verbose)?
+            describe(api_config, api_config.get_api_endpoint(endpoint).unwrap())
         }
+        Commands::Run(args) => run::run(args, &apis[&args.api], &config.session_directory())?,
+        Commands::Graphql { command } => match command {
+            GraphQLCommands::Introspect {
+                api,
+                context,
+                endpoint,
+                input,
+                output_file,
+            } => graphql::introspect(api, &apis[api], endpoint, context, input, output_file)?,
+        },
+        Commands::Flow { command } => match command {
+            FlowCommands::Run {
+                api,
+                flow,
+                context,
+                input,
+            } => flow::run(
+                api,
+                &apis[api],
+                flow,
+                context,
+                input,
+                &config.session_directory(),
+            )?,
+        },
     }
     Result::Ok(())
 }
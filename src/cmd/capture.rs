@@ -0,0 +1,121 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use regex::Regex;
+use serde_json::Value;
+
+use super::{config::APICapture, error::ExecutorError, executor::HttpResponse, filter::Filter};
+
+/// Values captured from the responses of an API, saved in a file per context
+/// so that later runs can use them in their templates.
+pub type Session = BTreeMap<String, String>;
+
+/// The file holding the session of an API in a context.
+pub fn session_file(directory: &Path, api: &str, context: &Option<String>) -> PathBuf {
+    match context {
+        Some(context) => directory.join(format!("{}.{}.yml", api, context)),
+        None => directory.join(format!("{}.yml", api)),
+    }
+}
+
+/// Reads a session, which is empty until something is captured.
+pub fn load(path: &Path) -> Result<Session, ExecutorError> {
+    if !path.exists() {
+        return Ok(Session::new());
+    }
+    let read_error = |e: String| ExecutorError::FailedToReadSession(path.display().to_string(), e);
+    let content = std::fs::read_to_string(path).map_err(|e| read_error(e.to_string()))?;
+    serde_yaml::from_str(&content).map_err(|e| read_error(e.to_string()))
+}
+
+/// Adds the values to the session, replacing the ones with the same name.
+pub fn save(path: &Path, values: Session) -> Result<(), ExecutorError> {
+    let write_error = |e: String| ExecutorError::FailedToWriteFile(path.display().to_string(), e);
+    let mut session = load(path)?;
+    session.extend(values);
+    if let Some(directory) = path.parent() {
+        std::fs::create_dir_all(directory).map_err(|e| write_error(e.to_string()))?;
+    }
+    let content = serde_yaml::to_string(&session).map_err(|e| write_error(e.to_string()))?;
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    // Sessions hold tokens, only the user may read them.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        if path.exists() {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+                .map_err(|e| write_error(e.to_string()))?;
+        }
+    }
+    let mut file = options.open(path).map_err(|e| write_error(e.to_string()))?;
+    file.write_all(content.as_bytes())
+        .map_err(|e| write_error(e.to_string()))
+}
+
+fn capture_one(
+    name: &str,
+    rule: &APICapture,
+    response: &HttpResponse,
+) -> Result<String, ExecutorError> {
+    let failed = |reason: String| ExecutorError::CaptureFailed(name.to_string(), reason);
+    let text = match (&rule.json, &rule.header) {
+        (Some(expression), _) => {
+            let body: Value = serde_json::from_slice(&response.body)
+                .map_err(|e| failed(format!("the body is not JSON: {}", e)))?;
+            let mut values: Vec<Value> = Filter::parse(expression)?
+                .apply(&body)?
+                .into_iter()
+                .filter(|v| !v.is_null())
+                .collect();
+            match values.len() {
+                0 => return Err(failed(format!("`{}` matched nothing", expression))),
+                1 => match values.remove(0) {
+                    Value::String(text) => text,
+                    value => value.to_string(),
+                },
+                _ => Value::Array(values).to_string(),
+            }
+        }
+        (None, Some(header)) => response
+            .headers
+            .iter()
+            .find(|h| h.key.eq_ignore_ascii_case(header))
+            .map(|h| h.value.clone())
+            .ok_or_else(|| failed(format!("no {} header", header)))?,
+        (None, None) => String::from_utf8_lossy(&response.body).into_owned(),
+    };
+    let pattern = match &rule.regex {
+        Some(pattern) => pattern,
+        None => return Ok(text),
+    };
+    let regex = Regex::new(pattern).map_err(|e| failed(e.to_string()))?;
+    let captures = regex
+        .captures(&text)
+        .ok_or_else(|| failed(format!("`{}` does not match", pattern)))?;
+    let matched = captures
+        .get(1)
+        .or_else(|| captures.get(0))
+        .map(|m| m.as_str());
+    Ok(matched.unwrap_or_default().to_string())
+}
+
+/// Reads every value the rules capture from a response.
+pub fn capture(
+    rules: &HashMap<String, APICapture>,
+    response: &HttpResponse,
+) -> Result<Session, ExecutorError> {
+    rules
+        .iter()
+        .map(|(name, rule)| Ok((name.clone(), capture_one(name, rule, response)?)))
+        .collect()
+}
+
+#[cfg(test)]
+#[path = "./capture_test.rs"]
+mod capture_test;
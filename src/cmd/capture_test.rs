@@ -0,0 +1,142 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use httpmock::Method::GET;
+    use httpmock::MockServer;
+
+    use crate::cmd::capture::{capture, load, save, session_file, Session};
+    use crate::cmd::config::{APICapture, APIConfig, APIEndpoint};
+    use crate::cmd::error::ExecutorError;
+    use crate::cmd::executor::{Engine, Header, HttpResponse};
+
+    fn rule(json: Option<&str>, header: Option<&str>, regex: Option<&str>) -> APICapture {
+        APICapture {
+            json: json.map(String::from),
+            header: header.map(String::from),
+            regex: regex.map(String::from),
+        }
+    }
+
+    fn response() -> HttpResponse {
+        HttpResponse {
+            status: 201,
+            version: "HTTP/1.1".to_string(),
+            headers: vec![Header {
+                key: "location".to_string(),
+                value: "/users/42".to_string(),
+            }],
+            body: br#"{"access_token": "abc", "expires_in": 60, "items": [{"id": 1}, {"id": 2}]}"#
+                .to_vec(),
+        }
+    }
+
+    #[test]
+    fn capture_rules_test() {
+        let rules = HashMap::from([
+            (
+                "token".to_string(),
+                rule(Some("$.access_token"), None, None),
+            ),
+            ("expires".to_string(), rule(Some(".expires_in"), None, None)),
+            ("ids".to_string(), rule(Some(".items[].id"), None, None)),
+            (
+                "id".to_string(),
+                rule(None, Some("Location"), Some(r"/(\d+)$")),
+            ),
+            (
+                "prefix".to_string(),
+                rule(None, None, Some(r#""access_\w+""#)),
+            ),
+        ]);
+        let captured = capture(&rules, &response()).unwrap();
+        let expected: Session = [
+            ("token", "abc"),
+            ("expires", "60"),
+            ("ids", "[1,2]"),
+            ("id", "42"),
+            ("prefix", "\"access_token\""),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        assert_eq!(captured, expected);
+
+        let missing = HashMap::from([(
+            "token".to_string(),
+            rule(Some(".refresh_token"), None, None),
+        )]);
+        assert!(matches!(
+            capture(&missing, &response()),
+            Err(ExecutorError::CaptureFailed(name, _)) if name == "token"
+        ));
+    }
+
+    #[test]
+    fn session_file_test() {
+        let directory = std::env::temp_dir().join(format!("rbm-session-{}", std::process::id()));
+        let path = session_file(&directory, "api", &Some("local".to_string()));
+        assert_eq!(path, directory.join("api.local.yml"));
+        assert!(load(&path).unwrap().is_empty());
+        save(
+            &path,
+            Session::from([("token".to_string(), "abc".to_string())]),
+        )
+        .unwrap();
+        save(&path, Session::from([("id".to_string(), "42".to_string())])).unwrap();
+        let session = load(&path).unwrap();
+        assert_eq!(session.get("token").map(String::as_str), Some("abc"));
+        assert_eq!(session.get("id").map(String::as_str), Some("42"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn session_reaches_later_requests_test() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/users/42")
+                .header("Authorization", "Bearer abc");
+            then.status(200);
+        });
+        let directory =
+            std::env::temp_dir().join(format!("rbm-session-run-{}", std::process::id()));
+        let path = session_file(&directory, "api", &None);
+        let login = HttpResponse {
+            body: br#"{"access_token": "abc"}"#.to_vec(),
+            ..response()
+        };
+        let rules = HashMap::from([
+            ("token".to_string(), rule(Some(".access_token"), None, None)),
+            (
+                "id".to_string(),
+                rule(None, Some("Location"), Some(r"/(\d+)$")),
+            ),
+        ]);
+        save(&path, capture(&rules, &login).unwrap()).unwrap();
+
+        let endpoint = APIEndpoint {
+            url: format!("http://localhost:{}/users/{{{{id}}}}", server.port()),
+            headers: Some(HashMap::from([(
+                "Authorization".to_string(),
+                "Bearer {{token}}".to_string(),
+            )])),
+            ..Default::default()
+        };
+        let api_config = APIConfig::new(None, HashMap::from([("user".to_string(), endpoint)]));
+        let mut engine = Engine::new();
+        engine.add_session(&load(&path).unwrap());
+        let (request, stream) = engine
+            .stream("api", &api_config, "user", &None, &[])
+            .unwrap();
+        assert_eq!(stream.into_exchange(request).unwrap().response.status, 200);
+        mock.assert();
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
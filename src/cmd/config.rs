@@ -1,13 +1,17 @@
 use std::{
-    collections::HashMap,
+    collections::{hash_map::Entry, HashMap, HashSet},
     fmt::{self},
     marker::PhantomData,
-    path::PathBuf,
+    path::{Path, PathBuf},
     str::FromStr,
 };
 
-
-use glob::glob;
+use clap::ValueEnum;
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{InstanceType, RootSchema, Schema, SchemaObject},
+    schema_for, JsonSchema,
+};
 use serde::{
     de::{self, MapAccess, Visitor},
     Deserialize, Deserializer, Serialize,
@@ -15,44 +19,274 @@ use serde::{
 
 use void::Void;
 
-#[derive(Serialize, Deserialize, Debug)]
+use super::error::ConfigError;
+
+mod discovery;
+pub mod editor;
+mod format;
+mod inheritance;
+
+use format::CollectionFormat;
+
+const DEFAULT_COLLECTION_DIRECTORY: &str = "apis";
+pub const PROJECT_DIRECTORY: &str = ".rbm";
+const PROJECT_FILE: &str = "rbm.yml";
+const INCLUDE_KEY: &str = "include";
+const SESSION_DIRECTORY: &str = "sessions";
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
 pub struct Config {
     #[serde(skip_deserializing, skip_serializing)]
     config_directory: PathBuf,
-    #[serde(rename = "api-collection-directories")]
+    #[serde(rename = "api-collection-directories", default)]
     api_collection_directory: Vec<PathBuf>,
     #[serde(rename = "global-context")]
     global_context: Option<HashMap<String, String>>,
+    #[serde(rename = "namespace-from-path", default)]
+    namespace_from_path: bool,
+    #[serde(skip)]
+    project: Option<Box<Config>>,
 }
 
 pub type APIContext = HashMap<String, String>;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct APIEndpoint {
     pub method: APIMethod,
     pub url: String,
     pub headers: Option<HashMap<String, String>>,
+    pub query: Option<HashMap<String, String>>,
+    #[serde(deserialize_with = "string_or_struct_opt", default)]
+    pub body: Option<APIBody>,
+    pub auth: Option<AuthEndpoint>,
+    pub client: Option<APIClient>,
+    pub inputs: Option<HashMap<String, APIInput>>,
+    pub output: Option<APIOutput>,
+    /// Statuses the response must have, `rbm run` exits with an error otherwise.
+    pub expect_status: Option<Vec<u16>>,
+    pub stream: Option<APIStream>,
+    #[serde(rename = "type", default)]
+    pub endpoint_type: APIEndpointType,
+    pub graphql: Option<APIGraphQL>,
+    pub grpc: Option<APIGrpc>,
+    pub capture: Option<HashMap<String, APICapture>>,
+}
+
+/// An endpoint as written in a collection file. Fields left out are inherited
+/// from the endpoint it `extends` and from the API `defaults` when the API is
+/// loaded, which turns it into an `APIEndpoint`.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+pub struct APIEndpointDefinition {
+    pub extends: Option<String>,
+    pub method: Option<APIMethod>,
+    pub url: Option<String>,
+    pub headers: Option<HashMap<String, String>>,
+    pub query: Option<HashMap<String, String>>,
     #[serde(deserialize_with = "string_or_struct_opt", default)]
+    #[schemars(schema_with = "string_or_body_schema")]
     pub body: Option<APIBody>,
-    pub auth: Option<AuthEndpoint>
+    pub auth: Option<AuthEndpoint>,
+    pub client: Option<APIClient>,
+    pub inputs: Option<HashMap<String, APIInput>>,
+    pub output: Option<APIOutput>,
+    /// Statuses the response must have, `rbm run` exits with an error otherwise.
+    pub expect_status: Option<Vec<u16>>,
+    pub stream: Option<APIStream>,
+    #[serde(rename = "type")]
+    pub endpoint_type: Option<APIEndpointType>,
+    /// The operation of a `type: graphql` endpoint, sent as the body.
+    pub graphql: Option<APIGraphQL>,
+    /// The method of a `type: grpc` endpoint.
+    pub grpc: Option<APIGrpc>,
+    /// Values read from a successful response and saved for the later
+    /// requests of the same API and context.
+    pub capture: Option<HashMap<String, APICapture>>,
+}
+
+/// Settings shared by every endpoint of an API.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+pub struct APIDefaults {
+    pub headers: Option<HashMap<String, String>>,
+    pub query: Option<HashMap<String, String>>,
+    pub auth: Option<AuthEndpoint>,
+    pub client: Option<APIClient>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+pub struct APIClient {
+    /// Request timeout in seconds.
+    pub timeout: Option<u64>,
+    /// Accept invalid TLS certificates.
+    pub insecure: Option<bool>,
+    #[serde(rename = "follow-redirects")]
+    pub follow_redirects: Option<bool>,
+}
+
+/// The protocol spoken by an endpoint on top of HTTP.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default, PartialEq)]
+pub enum APIEndpointType {
+    #[default]
+    #[serde(rename = "http")]
+    Http,
+    /// Sends the `graphql` operation as a JSON body, `method` defaults to POST.
+    #[serde(rename = "graphql")]
+    GraphQL,
+    /// Calls the `grpc` method with the body as a JSON message, the headers are
+    /// sent as metadata.
+    #[serde(rename = "grpc")]
+    Grpc,
+}
+
+/// A GraphQL operation.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct APIGraphQL {
+    /// The document, inline or read from `{type: file, content: path}`. It is
+    /// sent as written, values go in `variables`.
+    #[serde(deserialize_with = "string_or_struct")]
+    #[schemars(schema_with = "string_or_body_schema")]
+    pub query: APIBody,
+    #[serde(rename = "operationName")]
+    pub operation_name: Option<String>,
+    /// A map whose strings are templates, or a template rendering a JSON
+    /// object for variables that are not strings.
+    pub variables: Option<serde_json::Value>,
+}
+
+/// A gRPC method along with where its descriptors come from.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct APIGrpc {
+    /// `package.Service/Method`.
+    pub method: String,
+    /// `.proto` files describing the service, relative to the collection file.
+    /// The server reflection is asked when there are none.
+    pub protos: Option<Vec<PathBuf>>,
+    /// Where imports are looked up, the directory of each file by default.
+    #[serde(rename = "import-paths")]
+    pub import_paths: Option<Vec<PathBuf>>,
+    /// The collection file the section is written in, kept when an endpoint
+    /// inherits it.
+    #[serde(skip)]
+    pub source: Option<PathBuf>,
+}
+
+/// Where a captured value is read from, the whole body when neither `json`
+/// nor `header` is set.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct APICapture {
+    /// A filter on the JSON body, e.g. `$.access_token`. Several results are
+    /// saved as a JSON array.
+    pub json: Option<String>,
+    /// A response header.
+    pub header: Option<String>,
+    /// Keeps the first group of the first match, or the whole match.
+    pub regex: Option<String>,
+}
+
+/// Steps run one after the other by `rbm flow run`, the values captured by a
+/// step are available to the ones after it.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct APIFlow {
+    pub description: Option<String>,
+    pub steps: Vec<APIFlowStep>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct APIFlowStep {
+    /// Shown in the summary, the endpoint by default.
+    pub name: Option<String>,
+    pub endpoint: String,
+    /// Templates for the inputs of the endpoint.
+    pub inputs: Option<HashMap<String, String>>,
+    /// Added to the captures of the endpoint.
+    pub capture: Option<HashMap<String, APICapture>>,
+    /// A template, the step is skipped when it renders to an empty string,
+    /// `false` or `0`.
+    #[serde(rename = "if")]
+    pub condition: Option<String>,
+    /// A variable holding a JSON array, the step runs once per element.
+    pub for_each: Option<String>,
+    /// The variable holding the current element, `item` by default.
+    #[serde(rename = "as")]
+    pub item: Option<String>,
+}
+
+/// How the body of an endpoint is read.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub enum APIStream {
+    /// Print the body as it arrives, as with `--stream`.
+    #[serde(rename = "raw")]
+    Raw,
+    /// Read the body as Server-Sent Events, which is also picked for a
+    /// `text/event-stream` response.
+    #[serde(rename = "sse")]
+    Sse,
+}
+
+/// How the response of an endpoint is printed.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+pub struct APIOutput {
+    /// Default `--filter` applied to a JSON body, e.g. `.items[].id`.
+    pub filter: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct APIInput {
+    #[serde(rename = "type", default)]
+    pub input_type: APIInputType,
+    #[serde(default)]
+    pub required: bool,
+    pub default: Option<String>,
+    pub description: Option<String>,
+    pub values: Option<Vec<String>>,
+    pub pattern: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+pub enum APIInputType {
+    #[default]
+    #[serde(rename = "string")]
+    String,
+    #[serde(rename = "int")]
+    Int,
+    #[serde(rename = "bool")]
+    Bool,
+    #[serde(rename = "enum")]
+    Enum,
+    #[serde(rename = "regex")]
+    Regex,
+}
+
+impl fmt::Display for APIInputType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            APIInputType::String => "string",
+            APIInputType::Int => "int",
+            APIInputType::Bool => "bool",
+            APIInputType::Enum => "enum",
+            APIInputType::Regex => "regex",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(tag = "type")]
 pub enum AuthEndpoint {
     #[serde(rename = "basic")]
-    Basic{username: String, password: String}
+    Basic { username: String, password: String },
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct APIBody {
     #[serde(rename = "type")]
+    #[schemars(schema_with = "body_type_schema")]
     pub api_body_type: APIBodyType,
     #[serde(rename = "content")]
     pub content: String,
 }
 
 impl APIBody {
+    #[allow(dead_code)]
     pub fn new(content: &str, api_type: APIBodyType) -> Self {
         APIBody {
             api_body_type: api_type,
@@ -61,7 +295,8 @@ impl APIBody {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum APIBodyType {
     #[serde(alias = "file")]
     FILE,
@@ -69,30 +304,114 @@ pub enum APIBodyType {
     STRING,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, ValueEnum, Debug, Clone, Default)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum APIMethod {
+    #[default]
     GET,
     POST,
     DELETE,
     PATCH,
+    /// A WebSocket, the body is sent as the first message.
+    WS,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
 pub struct APIConfig {
     context: Option<HashMap<String, APIContext>>,
+    defaults: Option<APIDefaults>,
+    #[serde(rename = "endpoints")]
+    definitions: HashMap<String, APIEndpointDefinition>,
+    #[serde(skip)]
     endpoints: HashMap<String, APIEndpoint>,
+    flows: Option<HashMap<String, APIFlow>>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    merge: bool,
+    #[serde(skip)]
+    sources: Vec<PathBuf>,
 }
 
 impl APIConfig {
+    #[allow(dead_code)]
     pub fn new(
         context: Option<HashMap<String, APIContext>>,
         endpoints: HashMap<String, APIEndpoint>,
     ) -> APIConfig {
         APIConfig {
             context,
+            defaults: None,
+            definitions: HashMap::new(),
             endpoints,
+            flows: None,
+            merge: false,
+            sources: Vec::new(),
         }
     }
+
+    #[cfg(test)]
+    pub fn with_flows(mut self, flows: HashMap<String, APIFlow>) -> APIConfig {
+        self.flows = Some(flows);
+        self
+    }
+
+    /// Files the API was loaded from, more than one when it is merged.
+    pub fn get_sources(&self) -> &[PathBuf] {
+        &self.sources
+    }
+
+    /// Deep merges the endpoints, contexts and defaults of another definition of
+    /// the same API. Entries defined by both are conflicts, the first one is kept.
+    fn merge(&mut self, name: &str, other: APIConfig) -> Vec<ConfigError> {
+        let mut conflicts = Vec::new();
+        let first = self.sources.first().cloned().unwrap_or_default();
+        let second = other.sources.first().cloned().unwrap_or_default();
+        let conflict = |what: String| {
+            ConfigError::MergeConflict(name.to_string(), what, first.clone(), second.clone())
+        };
+        match (&self.defaults, other.defaults) {
+            (Some(_), Some(_)) => conflicts.push(conflict("defaults".to_string())),
+            (None, defaults) => self.defaults = defaults,
+            (Some(_), None) => (),
+        }
+        for (endpoint_name, endpoint) in other.definitions {
+            match self.definitions.entry(endpoint_name) {
+                Entry::Occupied(entry) => {
+                    conflicts.push(conflict(format!("endpoint {}", entry.key())))
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(endpoint);
+                }
+            }
+        }
+        for (flow_name, flow) in other.flows.unwrap_or_default() {
+            match self.flows.get_or_insert_with(HashMap::new).entry(flow_name) {
+                Entry::Occupied(entry) => conflicts.push(conflict(format!("flow {}", entry.key()))),
+                Entry::Vacant(entry) => {
+                    entry.insert(flow);
+                }
+            }
+        }
+        for (context_name, context) in other.context.unwrap_or_default() {
+            let merged = self
+                .context
+                .get_or_insert_with(HashMap::new)
+                .entry(context_name.clone())
+                .or_default();
+            for (key, value) in context {
+                match merged.get(&key) {
+                    Some(existing) if *existing != value => {
+                        conflicts.push(conflict(format!("context {} key {}", context_name, key)))
+                    }
+                    Some(_) => (),
+                    None => {
+                        merged.insert(key, value);
+                    }
+                }
+            }
+        }
+        self.sources.extend(other.sources);
+        conflicts
+    }
 }
 
 impl APIConfig {
@@ -103,14 +422,40 @@ impl APIConfig {
         self.endpoints.get(endpoint)
     }
     pub fn contains_context(&self, context: &str) -> bool {
-        return self
-            .context
+        self.context
             .as_ref()
             .map(|c| c.contains_key(context))
-            .unwrap_or(false);
+            .unwrap_or(false)
     }
     pub fn get_api_context(&self, context: &str) -> Option<&APIContext> {
-        return self.context.as_ref().and_then(|c| c.get(context));
+        self.context.as_ref().and_then(|c| c.get(context))
+    }
+    pub fn get_api_endpoints(&self) -> &HashMap<String, APIEndpoint> {
+        &self.endpoints
+    }
+    pub fn get_api_contexts(&self) -> Option<&HashMap<String, APIContext>> {
+        self.context.as_ref()
+    }
+    pub fn get_flow(&self, flow: &str) -> Option<&APIFlow> {
+        self.flows.as_ref().and_then(|f| f.get(flow))
+    }
+    pub fn get_flows(&self) -> Option<&HashMap<String, APIFlow>> {
+        self.flows.as_ref()
+    }
+    /// The endpoint as written, before `extends` and `defaults` apply.
+    pub fn get_definition(&self, endpoint: &str) -> Option<&APIEndpointDefinition> {
+        self.definitions.get(endpoint)
+    }
+    /// The endpoints whose `extends` names the given one, sorted.
+    pub fn get_extending(&self, endpoint: &str) -> Vec<&String> {
+        let mut names: Vec<&String> = self
+            .definitions
+            .iter()
+            .filter(|(_, definition)| definition.extends.as_deref() == Some(endpoint))
+            .map(|(name, _)| name)
+            .collect();
+        names.sort();
+        names
     }
 }
 
@@ -119,103 +464,388 @@ impl Config {
         self.config_directory = path;
     }
 
-    fn read_api(api_file: &PathBuf) -> HashMap<String, APIConfig> {
-        let error_msg = format!("Could not open api file {:?}", api_file);
-        let file_reader = std::fs::File::open(api_file).unwrap_or_else(|_| { panic!("{}", error_msg) });
-        serde_yaml::from_reader(file_reader).expect("Could not parse config file")
+    /// The `global-context` of the config, the project one wins for the keys
+    /// both define.
+    pub fn global_context(&self) -> HashMap<String, String> {
+        let mut values = self.global_context.clone().unwrap_or_default();
+        if let Some(project) = &self.project {
+            values.extend(project.global_context());
+        }
+        values
+    }
+
+    /// Where captured values are saved, next to the config file.
+    pub fn session_directory(&self) -> PathBuf {
+        self.config_directory.join(SESSION_DIRECTORY)
+    }
+
+    /// Reads a collection file along with the files it includes, the APIs of
+    /// an included file are merged into the ones of the file including it.
+    /// The canonical paths of the files included successfully are added to
+    /// `included`.
+    fn read_api(
+        api_file: &Path,
+        including: &mut Vec<PathBuf>,
+        included: &mut HashSet<PathBuf>,
+        errors: &mut Vec<ConfigError>,
+    ) -> Result<HashMap<String, APIConfig>, ConfigError> {
+        let io_error = |e: std::io::Error| ConfigError::Io(api_file.to_path_buf(), e.to_string());
+        let canonical = std::fs::canonicalize(api_file).map_err(io_error)?;
+        if including.contains(&canonical) {
+            let mut chain: Vec<String> =
+                including.iter().map(|p| p.display().to_string()).collect();
+            chain.push(canonical.display().to_string());
+            return Err(ConfigError::IncludeCycle(chain.join(" -> ")));
+        }
+        let format = CollectionFormat::from_path(api_file).unwrap_or(CollectionFormat::Yaml);
+        let content = std::fs::read_to_string(api_file).map_err(io_error)?;
+        let collection: CollectionFile = format.parse(api_file, &content)?;
+
+        let mut apis = collection.apis;
+        for api in apis.values_mut() {
+            api.sources = vec![api_file.to_path_buf()];
+            for grpc in api.definitions.values_mut().filter_map(|d| d.grpc.as_mut()) {
+                grpc.source = Some(api_file.to_path_buf());
+            }
+        }
+        including.push(canonical);
+        for include in collection.include {
+            let include_file = api_file.parent().unwrap_or(Path::new(".")).join(include);
+            let included_apis = Config::read_api(&include_file, including, included, errors)?;
+            if let Ok(canonical) = std::fs::canonicalize(&include_file) {
+                included.insert(canonical);
+            }
+            for (name, api) in included_apis {
+                match apis.get_mut(&name) {
+                    Some(existing) => errors.extend(existing.merge(&name, api)),
+                    None => {
+                        apis.insert(name, api);
+                    }
+                }
+            }
+        }
+        including.pop();
+        Ok(apis)
+    }
+
+    /// Adds the APIs of a collection file, an API already defined by another
+    /// file is only merged when every definition opts in with `merge: true`.
+    fn add_apis(
+        result: &mut HashMap<String, APIConfig>,
+        apis: HashMap<String, APIConfig>,
+        errors: &mut Vec<ConfigError>,
+    ) {
+        for (name, api) in apis {
+            match result.get_mut(&name) {
+                None => {
+                    result.insert(name, api);
+                }
+                Some(existing) if existing.merge && api.merge => {
+                    errors.extend(existing.merge(&name, api));
+                }
+                Some(existing) => errors.push(ConfigError::DuplicateAPI(
+                    name,
+                    existing.sources.first().cloned().unwrap_or_default(),
+                    api.sources.first().cloned().unwrap_or_default(),
+                )),
+            }
+        }
     }
 
-    pub fn read_apis(&self) -> HashMap<String, APIConfig> {
+    /// Loads every collection file, returning the APIs of the files that could
+    /// be read along with the errors of the ones that could not. APIs from the
+    /// project collections replace the ones with the same name.
+    pub fn read_apis(&self) -> (HashMap<String, APIConfig>, Vec<ConfigError>) {
+        let (mut result, mut errors) = self.read_collection_directories();
+        if let Some(project) = &self.project {
+            let (project_apis, project_errors) = project.read_apis();
+            result.extend(project_apis);
+            errors.extend(project_errors);
+        }
+        (result, errors)
+    }
+
+    fn absolute_collection_directories(&self) -> Vec<PathBuf> {
+        self.api_collection_directory
+            .iter()
+            .map(|api_directory| self.config_directory.join(api_directory))
+            .collect()
+    }
+
+    /// The collection directories, the project ones first.
+    pub fn collection_directories(&self) -> Vec<PathBuf> {
+        let mut directories = match &self.project {
+            Some(project) => project.collection_directories(),
+            None => Vec::new(),
+        };
+        directories.extend(self.absolute_collection_directories());
+        directories
+    }
+
+    fn read_collection_directories(&self) -> (HashMap<String, APIConfig>, Vec<ConfigError>) {
         let mut result: HashMap<String, APIConfig> = HashMap::new();
+        let mut errors: Vec<ConfigError> = Vec::new();
+        let mut included: HashSet<PathBuf> = HashSet::new();
+        let mut loaded = Vec::new();
 
-        for api_directory in self.api_collection_directory.iter() {
-            let mut abs_api_dir = if !api_directory.is_absolute() {
-                let mut abs_dir = self.config_directory.clone();
-                abs_dir.push(api_directory);
-                abs_dir
-            } else {
-                api_directory.clone()
-            };
+        for abs_api_dir in self.absolute_collection_directories() {
+            if !abs_api_dir.exists() {
+                continue;
+            }
             if !abs_api_dir.is_dir() {
-                println!(
-                    "Configured api collection directory: {:?} is not a folder",
-                    abs_api_dir.as_os_str()
-                );
-                std::process::exit(1)
+                errors.push(ConfigError::NotADirectory(abs_api_dir));
+                continue;
             }
-            if !abs_api_dir.exists() {
+            let (files, discovery_errors) = discovery::discover(&abs_api_dir);
+            errors.extend(discovery_errors);
+            for file in files {
+                let mut file_errors = Vec::new();
+                let apis =
+                    Config::read_api(&file.path, &mut Vec::new(), &mut included, &mut file_errors);
+                loaded.push((file, apis, file_errors));
+            }
+        }
+        // A file included by another one is only loaded through the include,
+        // a file linked from several places only once per namespace.
+        let mut seen: HashSet<(PathBuf, Option<String>)> = HashSet::new();
+        for (file, apis, file_errors) in loaded {
+            let canonical = std::fs::canonicalize(&file.path).unwrap_or_else(|_| file.path.clone());
+            let namespace = file.namespace.clone().filter(|_| self.namespace_from_path);
+            if included.contains(&canonical) || !seen.insert((canonical, namespace)) {
                 continue;
             }
-            abs_api_dir.push("*.y*ml");
-            for maybe_file in glob(abs_api_dir.to_str().unwrap()).expect("Error listing files") {
-                match maybe_file {
-                    Ok(file) => {
-                        let apis = Config::read_api(&file);
-                        result.extend(apis)
-                    }
-                    Err(_e) => {}
+            errors.extend(file_errors);
+            match apis {
+                Ok(apis) => {
+                    let apis = match (&file.namespace, self.namespace_from_path) {
+                        (Some(namespace), true) => apis
+                            .into_iter()
+                            .map(|(name, api)| (format!("{}.{}", namespace, name), api))
+                            .collect(),
+                        _ => apis,
+                    };
+                    Config::add_apis(&mut result, apis, &mut errors)
                 }
+                Err(e) => errors.push(e),
             }
         }
-        result
+        for (name, api) in result.iter_mut() {
+            errors.extend(api.resolve_endpoints(name));
+        }
+        (result, errors)
     }
 }
 
-pub fn read_config_or_create_default(maybe_config: &Option<PathBuf>) -> Config {
-    match maybe_config {
+pub fn read_config_or_create_default(
+    maybe_config: &Option<PathBuf>,
+) -> Result<Config, ConfigError> {
+    let mut config = match maybe_config {
         Some(file) => read_config(file),
         None => read_default_config(),
+    }?;
+    let working_directory =
+        std::env::current_dir().map_err(|e| ConfigError::Io(PathBuf::from("."), e.to_string()))?;
+    config.project = find_project(&working_directory)?.map(Box::new);
+    Ok(config)
+}
+
+/// Walks up from the working directory, the way git finds `.git`, looking for
+/// a `.rbm/` collection directory or a `rbm.yml` project file.
+fn find_project(working_directory: &Path) -> Result<Option<Config>, ConfigError> {
+    for directory in working_directory.ancestors() {
+        let project_file = directory.join(PROJECT_FILE);
+        let has_project_directory = directory.join(PROJECT_DIRECTORY).is_dir();
+        if !project_file.is_file() && !has_project_directory {
+            continue;
+        }
+        let mut project = if project_file.is_file() {
+            read_config(&project_file)?
+        } else {
+            Config {
+                api_collection_directory: Vec::new(),
+                ..create_default_config(directory.to_path_buf())
+            }
+        };
+        project
+            .api_collection_directory
+            .retain(|d| d != Path::new(PROJECT_DIRECTORY));
+        if has_project_directory {
+            project
+                .api_collection_directory
+                .push(PROJECT_DIRECTORY.into());
+        }
+        return Ok(Some(project));
     }
+    Ok(None)
 }
 
-fn default_rbm_directory() -> PathBuf {
-    let mut config_directory =
-        dirs::config_dir().expect("Could not read the default config directory");
+fn default_rbm_directory() -> Result<PathBuf, ConfigError> {
+    let mut config_directory = dirs::config_dir().ok_or(ConfigError::NoConfigDirectory)?;
     config_directory.push("rbm");
-    config_directory
+    Ok(config_directory)
 }
 
-fn create_default_config() -> Config {
-    let apis: Vec<PathBuf> = vec!["apis".into()];
+fn create_default_config(config_directory: PathBuf) -> Config {
+    let apis: Vec<PathBuf> = vec![DEFAULT_COLLECTION_DIRECTORY.into()];
     Config {
-        config_directory: default_rbm_directory(),
+        config_directory,
         api_collection_directory: apis,
         global_context: None,
+        namespace_from_path: false,
+        project: None,
     }
 }
 
-fn write_config(path: PathBuf, config: &Config) {
+fn write_config(path: PathBuf, config: &Config) -> Result<(), ConfigError> {
     let file_writer = std::fs::OpenOptions::new()
         .write(true)
         .create(true)
-        .open(path)
-        .expect("Could not write into config file");
-    serde_yaml::to_writer(file_writer, config).expect("Could not write into config file")
+        .truncate(true)
+        .open(&path)
+        .map_err(|e| ConfigError::Write(path.clone(), e.to_string()))?;
+    serde_yaml::to_writer(file_writer, config).map_err(|e| ConfigError::Write(path, e.to_string()))
 }
 
-fn read_default_config() -> Config {
-    let config_directory = default_rbm_directory();
-    if !config_directory.exists() {
-        std::fs::create_dir(&config_directory).unwrap();
+/// The config file used when none is given on the command line.
+pub fn default_config_file() -> Result<PathBuf, ConfigError> {
+    Ok(default_rbm_directory()?.join("config"))
+}
+
+/// Writes a default config file unless there is one already, returning the
+/// config in the file.
+pub fn init_config(config_file: &Path) -> Result<Config, ConfigError> {
+    if config_file.exists() {
+        return read_config(&config_file.to_path_buf());
     }
-    let mut config_file_path: PathBuf = config_directory;
-    config_file_path.push("config");
+    let config_directory = config_file.parent().unwrap_or(Path::new(".")).to_path_buf();
+    std::fs::create_dir_all(&config_directory)
+        .map_err(|e| ConfigError::Write(config_directory.clone(), e.to_string()))?;
+    write_config(
+        config_file.to_path_buf(),
+        &create_default_config(config_directory),
+    )?;
+    read_config(&config_file.to_path_buf())
+}
+
+/// Reads the default config file, falling back to the default config when
+/// there is none yet, `rbm init` is the one writing it.
+fn read_default_config() -> Result<Config, ConfigError> {
+    let config_file_path = default_config_file()?;
     if config_file_path.exists() {
         return read_config(&config_file_path);
     }
-    let default_config = create_default_config();
-    write_config(config_file_path, &default_config);
-    default_config
+    let mut config_directory = config_file_path;
+    config_directory.pop();
+    Ok(create_default_config(config_directory))
 }
 
-fn read_config(file_config: &PathBuf) -> Config {
-    let file_reader = std::fs::File::open(file_config).expect("Could not open config file");
-    let mut config: Config =
-        serde_yaml::from_reader(file_reader).expect("Could not parse config file");
-    let mut file_directory = std::fs::canonicalize(file_config).unwrap();
+fn read_config(file_config: &PathBuf) -> Result<Config, ConfigError> {
+    let file_reader = std::fs::File::open(file_config)
+        .map_err(|e| ConfigError::Io(file_config.clone(), e.to_string()))?;
+    let mut config: Config = serde_yaml::from_reader(file_reader)
+        .map_err(|e| ConfigError::from_yaml(file_config, &e))?;
+    let mut file_directory = std::fs::canonicalize(file_config)
+        .map_err(|e| ConfigError::Io(file_config.clone(), e.to_string()))?;
     file_directory.pop();
     config.set_config_path(file_directory);
-    config
+    Ok(config)
+}
+
+/// A collection file maps API names to their configuration, except for the
+/// `include` key which lists other collection files to pull in.
+struct CollectionFile {
+    include: Vec<PathBuf>,
+    apis: HashMap<String, APIConfig>,
+}
+
+impl<'de> Deserialize<'de> for CollectionFile {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct CollectionFileVisitor;
+
+        impl<'de> Visitor<'de> for CollectionFileVisitor {
+            type Value = CollectionFile;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map of API names to their configuration")
+            }
+
+            fn visit_map<M>(self, mut map: M) -> Result<CollectionFile, M::Error>
+            where
+                M: MapAccess<'de>,
+            {
+                let mut include = Vec::new();
+                let mut apis = HashMap::new();
+                while let Some(key) = map.next_key::<String>()? {
+                    if key == INCLUDE_KEY {
+                        include = map.next_value()?;
+                    } else {
+                        let api: APIConfig = map.next_value()?;
+                        apis.insert(key, api);
+                    }
+                }
+                Ok(CollectionFile { include, apis })
+            }
+        }
+
+        deserializer.deserialize_map(CollectionFileVisitor)
+    }
+}
+
+impl JsonSchema for CollectionFile {
+    fn schema_name() -> String {
+        "CollectionFile".to_string()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let mut schema = SchemaObject {
+            instance_type: Some(InstanceType::Object.into()),
+            ..Default::default()
+        };
+        let object = schema.object();
+        object
+            .properties
+            .insert(INCLUDE_KEY.to_string(), gen.subschema_for::<Vec<PathBuf>>());
+        object.additional_properties = Some(Box::new(gen.subschema_for::<APIConfig>()));
+        schema.into()
+    }
+}
+
+/// A body is either the content as a plain string or an `APIBody` map.
+fn string_or_body_schema(gen: &mut SchemaGenerator) -> Schema {
+    let mut schema = SchemaObject::default();
+    schema.subschemas().any_of = Some(vec![
+        gen.subschema_for::<String>(),
+        gen.subschema_for::<APIBody>(),
+    ]);
+    schema.into()
+}
+
+/// The body types as derived from `APIBodyType`, along with their lowercase
+/// serde aliases.
+fn body_type_schema(gen: &mut SchemaGenerator) -> Schema {
+    let mut schema = APIBodyType::json_schema(gen).into_object();
+    if let Some(values) = &mut schema.enum_values {
+        let aliases: Vec<serde_json::Value> = values
+            .iter()
+            .filter_map(|v| v.as_str())
+            .map(|v| v.to_lowercase().into())
+            .collect();
+        values.extend(aliases);
+    }
+    schema.into()
+}
+
+/// JSON Schema of the rbm config file.
+pub fn config_schema() -> RootSchema {
+    schema_for!(Config)
+}
+
+/// JSON Schema of a collection file.
+pub fn collection_schema() -> RootSchema {
+    schema_for!(CollectionFile)
 }
 
 fn string_or_struct_opt<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
@@ -300,3 +930,7 @@ where
 
     deserializer.deserialize_any(StringOrStruct(PhantomData))
 }
+
+#[cfg(test)]
+#[path = "./config_test.rs"]
+mod config_test;
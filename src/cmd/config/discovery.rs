@@ -0,0 +1,130 @@
+use std::path::{Path, PathBuf};
+
+use glob::Pattern;
+
+use crate::cmd::error::ConfigError;
+
+use super::format::CollectionFormat;
+
+const IGNORE_FILE: &str = ".rbmignore";
+
+/// A collection file found under an api collection directory.
+pub struct CollectionFile {
+    pub path: PathBuf,
+    /// Sub directories between the collection directory and the file, joined
+    /// with dots, e.g. `payments` for `payments/refunds.yml`.
+    pub namespace: Option<String>,
+}
+
+/// An ignore pattern along with the directory of the `.rbmignore` file that
+/// declared it, patterns are matched relative to that directory.
+#[derive(Clone)]
+struct IgnoreRule {
+    base: PathBuf,
+    pattern: Pattern,
+}
+
+impl IgnoreRule {
+    fn matches(&self, path: &Path) -> bool {
+        let relative = path.strip_prefix(&self.base).unwrap_or(path);
+        let name = path.file_name().map(Path::new).unwrap_or(relative);
+        self.pattern.matches_path(relative) || self.pattern.matches_path(name)
+    }
+}
+
+/// Every YAML, TOML, JSON and JSON5 file is a collection, request payloads
+/// kept next to them are left out with a `.rbmignore` file.
+pub fn is_collection_file(path: &Path) -> bool {
+    CollectionFormat::from_path(path).is_some()
+}
+
+/// Walks a collection directory recursively, following symlinks, and returns
+/// the collection files sorted by path.
+pub fn discover(root: &Path) -> (Vec<CollectionFile>, Vec<ConfigError>) {
+    let mut files = Vec::new();
+    let mut errors = Vec::new();
+    walk(root, &[], &[], &mut Vec::new(), &mut files, &mut errors);
+    (files, errors)
+}
+
+fn walk(
+    directory: &Path,
+    namespace: &[String],
+    inherited_rules: &[IgnoreRule],
+    ancestors: &mut Vec<PathBuf>,
+    files: &mut Vec<CollectionFile>,
+    errors: &mut Vec<ConfigError>,
+) {
+    // Symlinked directories may point back to one of their parents, the same
+    // directory reached through different links is walked each time.
+    let canonical = match std::fs::canonicalize(directory) {
+        Ok(canonical) => canonical,
+        Err(e) => return errors.push(ConfigError::Io(directory.to_path_buf(), e.to_string())),
+    };
+    if ancestors.contains(&canonical) {
+        return;
+    }
+    let mut rules = inherited_rules.to_vec();
+    rules.extend(read_ignore_file(directory, errors));
+
+    let mut entries: Vec<PathBuf> = match std::fs::read_dir(directory) {
+        Ok(entries) => entries.filter_map(|e| e.ok()).map(|e| e.path()).collect(),
+        Err(e) => return errors.push(ConfigError::Io(directory.to_path_buf(), e.to_string())),
+    };
+    entries.sort();
+    ancestors.push(canonical);
+    for path in entries {
+        if rules.iter().any(|r| r.matches(&path)) {
+            continue;
+        }
+        if path.is_dir() {
+            let mut child_namespace = namespace.to_vec();
+            child_namespace.push(path.file_name().unwrap().to_string_lossy().to_string());
+            walk(&path, &child_namespace, &rules, ancestors, files, errors);
+        } else if path.is_file() && is_collection_file(&path) {
+            files.push(CollectionFile {
+                path,
+                namespace: if namespace.is_empty() {
+                    None
+                } else {
+                    Some(namespace.join("."))
+                },
+            });
+        }
+    }
+    ancestors.pop();
+}
+
+fn read_ignore_file(directory: &Path, errors: &mut Vec<ConfigError>) -> Vec<IgnoreRule> {
+    let path = directory.join(IGNORE_FILE);
+    if !path.is_file() {
+        return Vec::new();
+    }
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) => {
+            errors.push(ConfigError::Io(path, e.to_string()));
+            return Vec::new();
+        }
+    };
+    let mut rules = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim().trim_end_matches('/');
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match Pattern::new(line) {
+            Ok(pattern) => rules.push(IgnoreRule {
+                base: directory.to_path_buf(),
+                pattern,
+            }),
+            Err(e) => errors.push(ConfigError::Parse(
+                path.clone(),
+                Some(index + 1),
+                Some(e.pos + 1),
+                e.msg.to_string(),
+            )),
+        }
+    }
+    rules
+}
@@ -0,0 +1,401 @@
+use std::path::{Path, PathBuf};
+
+use crate::cmd::error::ConfigError;
+
+use super::{format::CollectionFormat, APIConfig};
+
+const INDENT: usize = 2;
+
+/// A block style YAML document edited line by line, so the comments, the key
+/// order and the formatting of everything that is not touched are kept.
+pub struct YamlDocument {
+    path: PathBuf,
+    lines: Vec<String>,
+}
+
+/// The line of a key and the lines of its value, `end` is exclusive and
+/// stops after the last non blank, non comment line of the value.
+#[derive(Clone, Copy)]
+struct Block {
+    start: usize,
+    end: usize,
+    indent: usize,
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+fn is_content(line: &str) -> bool {
+    let trimmed = line.trim();
+    !trimmed.is_empty() && !trimmed.starts_with('#')
+}
+
+/// The key of a `key: value` line, with the quotes removed.
+fn key_of(line: &str) -> Option<&str> {
+    let trimmed = line.trim();
+    if trimmed.starts_with('-') {
+        return None;
+    }
+    let (key, rest) = match trimmed.chars().next()? {
+        quote @ ('"' | '\'') => {
+            let end = trimmed[1..].find(quote)? + 1;
+            (&trimmed[1..end], &trimmed[end + 1..])
+        }
+        _ => {
+            let end = trimmed.find(':')?;
+            (trimmed[..end].trim_end(), &trimmed[end..])
+        }
+    };
+    let rest = rest.strip_prefix(':')?;
+    if rest.is_empty() || rest.starts_with(' ') {
+        Some(key)
+    } else {
+        None
+    }
+}
+
+/// The inline value of a `key: value` line, empty when the value is a block.
+fn inline_value(line: &str) -> &str {
+    let trimmed = line.trim();
+    let key_end = match trimmed.chars().next() {
+        Some(quote @ ('"' | '\'')) => trimmed[1..].find(quote).map(|i| i + 2).unwrap_or(0),
+        _ => 0,
+    };
+    trimmed[key_end..]
+        .find(':')
+        .map(|i| trimmed[key_end + i + 1..].trim())
+        .unwrap_or("")
+}
+
+/// The comment ending a line, from its `#`, outside of quoted scalars.
+fn trailing_comment(line: &str) -> Option<&str> {
+    let mut quote = None;
+    let mut previous = ' ';
+    for (index, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '#') if previous.is_whitespace() && index > 0 => return Some(&line[index..]),
+            _ => (),
+        }
+        previous = c;
+    }
+    None
+}
+
+/// Quotes a scalar when YAML needs it, e.g. for templates starting with `{{`.
+pub fn scalar(value: &str) -> String {
+    serde_yaml::to_string(value)
+        .map(|s| s.trim_end().to_string())
+        .unwrap_or_else(|_| format!("'{}'", value.replace('\'', "''")))
+}
+
+impl YamlDocument {
+    pub fn open(path: &Path) -> Result<YamlDocument, ConfigError> {
+        if !matches!(
+            CollectionFormat::from_path(path),
+            Some(CollectionFormat::Yaml)
+        ) {
+            return Err(ConfigError::Edit(
+                path.to_path_buf(),
+                "only YAML collection files can be edited".to_string(),
+            ));
+        }
+        let content = if path.exists() {
+            std::fs::read_to_string(path)
+                .map_err(|e| ConfigError::Io(path.to_path_buf(), e.to_string()))?
+        } else {
+            String::new()
+        };
+        Ok(YamlDocument {
+            path: path.to_path_buf(),
+            lines: content.lines().map(String::from).collect(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn save(&self) -> Result<(), ConfigError> {
+        let mut content = self.lines.join("\n");
+        content.push('\n');
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| ConfigError::Write(parent.to_path_buf(), e.to_string()))?;
+        }
+        std::fs::write(&self.path, content)
+            .map_err(|e| ConfigError::Write(self.path.clone(), e.to_string()))
+    }
+
+    fn error(&self, message: String) -> ConfigError {
+        ConfigError::Edit(self.path.clone(), message)
+    }
+
+    fn last_content_line(&self, from: usize, to: usize) -> Option<usize> {
+        (from..to).rev().find(|i| is_content(&self.lines[*i]))
+    }
+
+    /// The blocks directly under a parent block, or the top level ones.
+    fn children(&self, parent: Option<Block>) -> Vec<Block> {
+        let (from, to) = match parent {
+            Some(block) => (block.start + 1, block.end),
+            None => (0, self.lines.len()),
+        };
+        let child_indent = match (from..to).find(|i| is_content(&self.lines[*i])) {
+            Some(first) => indent_of(&self.lines[first]),
+            None => return Vec::new(),
+        };
+        let starts: Vec<usize> = (from..to)
+            .filter(|i| {
+                let line = &self.lines[*i];
+                is_content(line) && indent_of(line) == child_indent && key_of(line).is_some()
+            })
+            .collect();
+        starts
+            .iter()
+            .enumerate()
+            .map(|(index, start)| {
+                let next = starts.get(index + 1).copied().unwrap_or(to);
+                let end = self
+                    .last_content_line(start + 1, next)
+                    .filter(|last| indent_of(&self.lines[*last]) > child_indent)
+                    .map(|last| last + 1)
+                    .unwrap_or(start + 1);
+                Block {
+                    start: *start,
+                    end,
+                    indent: child_indent,
+                }
+            })
+            .collect()
+    }
+
+    fn find(&self, path: &[&str]) -> Option<Block> {
+        let mut block: Option<Block> = None;
+        for key in path {
+            block = Some(
+                self.children(block)
+                    .into_iter()
+                    .find(|b| key_of(&self.lines[b.start]) == Some(*key))?,
+            );
+        }
+        block
+    }
+
+    pub fn contains(&self, path: &[&str]) -> bool {
+        self.find(path).is_some()
+    }
+
+    /// The key of an API in the document, which leaves out the namespace the
+    /// API gets from its directories with `namespace-from-path`.
+    fn api_key<'a>(&self, api: &'a str) -> Option<&'a str> {
+        std::iter::once(api)
+            .chain(api.match_indices('.').map(|(i, _)| &api[i + 1..]))
+            .find(|key| self.contains(&[key]))
+    }
+
+    /// Opens the YAML file of an API that holds `path` under the API, or its
+    /// first YAML file when none does, along with the key of the API there.
+    pub fn open_api(
+        api: &str,
+        api_config: &APIConfig,
+        path: &[&str],
+    ) -> Result<(YamlDocument, String), ConfigError> {
+        let mut documents = Vec::new();
+        for source in api_config.get_sources() {
+            if let Ok(document) = YamlDocument::open(source) {
+                if let Some(key) = document.api_key(api) {
+                    let key = key.to_string();
+                    documents.push((document, key));
+                }
+            }
+        }
+        let position = documents
+            .iter()
+            .position(|(document, key)| {
+                let mut full_path = vec![key.as_str()];
+                full_path.extend_from_slice(path);
+                document.contains(&full_path)
+            })
+            .unwrap_or(0);
+        match documents.len() > position {
+            true => Ok(documents.swap_remove(position)),
+            false => Err(ConfigError::Edit(
+                api_config
+                    .get_sources()
+                    .first()
+                    .cloned()
+                    .unwrap_or_default(),
+                format!("API {} is not defined in a YAML file", api),
+            )),
+        }
+    }
+
+    /// Indentation of the children of a block, creating the missing parents.
+    fn ensure(&mut self, path: &[&str]) -> Result<(Option<Block>, usize), ConfigError> {
+        let step = self.step();
+        let mut parent: Option<Block> = None;
+        for (depth, key) in path.iter().enumerate() {
+            let block = match self.find(&path[..=depth]) {
+                Some(block) => block,
+                None => {
+                    let indent = self.child_indent(parent, step)?;
+                    let at = self.insert_position(parent);
+                    self.lines
+                        .insert(at, format!("{}{}:", " ".repeat(indent), key));
+                    self.find(&path[..=depth]).unwrap()
+                }
+            };
+            parent = Some(block);
+        }
+        let indent = self.child_indent(parent, step)?;
+        Ok((parent, indent))
+    }
+
+    fn child_indent(&mut self, parent: Option<Block>, step: usize) -> Result<usize, ConfigError> {
+        let parent = match parent {
+            Some(parent) => parent,
+            None => return Ok(0),
+        };
+        if let Some(child) = self.children(Some(parent)).first() {
+            return Ok(child.indent);
+        }
+        let line = self.lines[parent.start].clone();
+        match inline_value(&line) {
+            "" => (),
+            "{}" | "null" | "~" => {
+                let colon = line.rfind(':').unwrap();
+                self.lines[parent.start] = line[..=colon].to_string();
+            }
+            _ => {
+                return Err(self.error(format!(
+                    "`{}` has an inline value and cannot hold new entries",
+                    line.trim()
+                )))
+            }
+        }
+        Ok(parent.indent + step)
+    }
+
+    fn insert_position(&self, parent: Option<Block>) -> usize {
+        match parent {
+            Some(block) => block.end,
+            None => self
+                .last_content_line(0, self.lines.len())
+                .map(|last| last + 1)
+                .unwrap_or(0),
+        }
+    }
+
+    /// The indentation step of the document, the smallest one in use.
+    fn step(&self) -> usize {
+        self.lines
+            .iter()
+            .filter(|line| is_content(line))
+            .map(|line| indent_of(line))
+            .filter(|indent| *indent > 0)
+            .min()
+            .unwrap_or(INDENT)
+    }
+
+    /// Appends a YAML snippet, written without indentation and with a two
+    /// spaces step, at the end of the block at `path`. The snippet is indented
+    /// the way the document is.
+    pub fn insert(&mut self, path: &[&str], snippet: &str) -> Result<(), ConfigError> {
+        let step = self.step();
+        let (parent, indent) = self.ensure(path)?;
+        let at = self.insert_position(parent);
+        let mut new_lines: Vec<String> = snippet
+            .lines()
+            .map(|line| match line.is_empty() {
+                true => String::new(),
+                false => {
+                    let own = indent_of(line);
+                    let width = indent + own / INDENT * step + own % INDENT;
+                    format!("{}{}", " ".repeat(width), line.trim_start_matches(' '))
+                }
+            })
+            .collect();
+        if parent.is_none() && at > 0 {
+            new_lines.insert(0, String::new());
+        }
+        self.lines.splice(at..at, new_lines);
+        Ok(())
+    }
+
+    /// Sets `key: value` under `path`, replacing the current value if any.
+    pub fn set(&mut self, path: &[&str], key: &str, value: &str) -> Result<(), ConfigError> {
+        let mut full_path = path.to_vec();
+        full_path.push(key);
+        match self.find(&full_path) {
+            Some(block) => {
+                let mut line = format!("{}{}: {}", " ".repeat(block.indent), scalar(key), value);
+                if let Some(comment) = trailing_comment(&self.lines[block.start]) {
+                    line = format!("{} {}", line, comment);
+                }
+                self.lines.splice(block.start..block.end, [line]);
+                Ok(())
+            }
+            None => self.insert(path, &format!("{}: {}", scalar(key), value)),
+        }
+    }
+
+    fn expect(&self, path: &[&str]) -> Result<Block, ConfigError> {
+        self.find(path)
+            .ok_or_else(|| self.error(format!("{} not found", path.join("."))))
+    }
+
+    /// Removes the block at `path`, a parent left without entries becomes an
+    /// empty map.
+    pub fn remove(&mut self, path: &[&str]) -> Result<(), ConfigError> {
+        let block = self.expect(path)?;
+        self.lines.drain(block.start..block.end);
+        let parent = match path.len() > 1 {
+            true => self.find(&path[..path.len() - 1]),
+            false => None,
+        };
+        if let Some(parent) = parent {
+            if self.children(Some(parent)).is_empty() {
+                self.lines[parent.start].push_str(" {}");
+            }
+        }
+        Ok(())
+    }
+
+    /// The block at `path` as a snippet, the way `insert` takes it.
+    pub fn snippet(&self, path: &[&str]) -> Result<String, ConfigError> {
+        let block = self.expect(path)?;
+        let step = self.step();
+        let lines: Vec<String> = self.lines[block.start..block.end]
+            .iter()
+            .map(|line| {
+                let own = indent_of(line).saturating_sub(block.indent);
+                let width = own / step * INDENT + own % step;
+                match line.trim().is_empty() {
+                    true => String::new(),
+                    false => format!("{}{}", " ".repeat(width), line.trim()),
+                }
+            })
+            .collect();
+        Ok(lines.join("\n"))
+    }
+
+    /// Renames the key of the block at `path`, keeping its value in place.
+    pub fn rename(&mut self, path: &[&str], new_key: &str) -> Result<(), ConfigError> {
+        let block = self.expect(path)?;
+        let line = &self.lines[block.start];
+        let value = inline_value(line);
+        let renamed = match value.is_empty() {
+            true => format!("{}{}:", " ".repeat(block.indent), scalar(new_key)),
+            false => format!("{}{}: {}", " ".repeat(block.indent), scalar(new_key), value),
+        };
+        self.lines[block.start] = renamed;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[path = "./editor_test.rs"]
+mod editor_test;
@@ -0,0 +1,102 @@
+#[cfg(test)]
+mod tests {
+    use crate::cmd::config::editor::{scalar, YamlDocument};
+    use crate::cmd::error::ConfigError;
+
+    fn document(name: &str, content: &str) -> YamlDocument {
+        let path =
+            std::env::temp_dir().join(format!("rbm-editor-{}-{}.yml", name, std::process::id()));
+        std::fs::write(&path, content).unwrap();
+        YamlDocument::open(&path).unwrap()
+    }
+
+    fn content(document: &YamlDocument) -> String {
+        document.save().unwrap();
+        std::fs::read_to_string(document.path()).unwrap()
+    }
+
+    #[test]
+    fn insert_keeps_comments_and_order_test() {
+        let mut document = document(
+            "insert",
+            "# pets\npets:\n    context:\n        local: {url: localhost}\n    endpoints:\n        # all of them\n        list:\n            method: GET\n            url: \"{{url}}/pets\"\n\n# end\n",
+        );
+        assert!(document.contains(&["pets", "endpoints", "list"]));
+        assert!(!document.contains(&["pets", "list"]));
+        document
+            .insert(
+                &["pets", "endpoints"],
+                &format!("get:\n  method: GET\n  url: {}\n", scalar("{{url}}/pets/1")),
+            )
+            .unwrap();
+        document.insert(&[], "cats:\n  endpoints: {}\n").unwrap();
+        document
+            .insert(&["cats", "endpoints"], "list:\n  method: GET\n")
+            .unwrap();
+        document
+            .insert(&["cats", "context", "local"], "url: localhost\n")
+            .unwrap();
+        assert_eq!(
+            content(&document),
+            "# pets\npets:\n    context:\n        local: {url: localhost}\n    endpoints:\n        # all of them\n        list:\n            method: GET\n            url: \"{{url}}/pets\"\n        get:\n            method: GET\n            url: '{{url}}/pets/1'\n\ncats:\n    endpoints:\n        list:\n            method: GET\n    context:\n        local:\n            url: localhost\n\n# end\n"
+        );
+    }
+
+    #[test]
+    fn insert_into_inline_value_fails_test() {
+        let mut document = document("inline", "pets:\n  endpoints: [list]\n");
+        assert!(matches!(
+            document.insert(&["pets", "endpoints"], "get:\n  method: GET\n"),
+            Err(ConfigError::Edit(_, _))
+        ));
+        assert!(matches!(
+            YamlDocument::open(std::path::Path::new("pets.json")),
+            Err(ConfigError::Edit(_, _))
+        ));
+    }
+
+    #[test]
+    fn set_remove_and_rename_test() {
+        let mut document = document(
+            "remove",
+            "pets:\n  context:\n    local:\n      url: localhost # dev\n  endpoints:\n    list:\n      method: GET\n      body: |\n        {\n          \"a\": 1\n        }\n",
+        );
+        document
+            .set(&["pets", "context", "local"], "url", "remote")
+            .unwrap();
+        document
+            .set(&["pets", "context", "local"], "token", &scalar("{{x}}"))
+            .unwrap();
+        assert_eq!(
+            document.snippet(&["pets", "endpoints", "list"]).unwrap(),
+            "list:\n  method: GET\n  body: |\n    {\n      \"a\": 1\n    }"
+        );
+        document
+            .rename(&["pets", "endpoints", "list"], "all")
+            .unwrap();
+        document.remove(&["pets", "endpoints", "all"]).unwrap();
+        assert!(document.remove(&["pets", "endpoints", "all"]).is_err());
+        assert_eq!(
+            content(&document),
+            "pets:\n  context:\n    local:\n      url: remote # dev\n      token: '{{x}}'\n  endpoints: {}\n"
+        );
+    }
+
+    #[test]
+    fn set_keeps_trailing_comments_test() {
+        let mut document = document(
+            "comment",
+            "pets:\n  context:\n    local:\n      url: 'http://host/#top' # dev\n      key: \"a#b\"\n",
+        );
+        document
+            .set(&["pets", "context", "local"], "url", "remote")
+            .unwrap();
+        document
+            .set(&["pets", "context", "local"], "key", "c")
+            .unwrap();
+        assert_eq!(
+            content(&document),
+            "pets:\n  context:\n    local:\n      url: remote # dev\n      key: c\n"
+        );
+    }
+}
@@ -0,0 +1,60 @@
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+
+use crate::cmd::error::ConfigError;
+
+/// The file formats a collection can be written in, picked by extension.
+pub enum CollectionFormat {
+    Yaml,
+    Toml,
+    Json,
+    Json5,
+}
+
+impl CollectionFormat {
+    pub fn from_path(path: &Path) -> Option<CollectionFormat> {
+        match path.extension()?.to_str()? {
+            "yml" | "yaml" => Some(CollectionFormat::Yaml),
+            "toml" => Some(CollectionFormat::Toml),
+            "json" => Some(CollectionFormat::Json),
+            "json5" => Some(CollectionFormat::Json5),
+            _ => None,
+        }
+    }
+
+    pub fn parse<T: DeserializeOwned>(&self, path: &Path, content: &str) -> Result<T, ConfigError> {
+        let parse_error = |line: Option<usize>, column: Option<usize>, message: String| {
+            ConfigError::Parse(path.to_path_buf(), line, column, message)
+        };
+        match self {
+            CollectionFormat::Yaml => {
+                serde_yaml::from_str(content).map_err(|e| ConfigError::from_yaml(path, &e))
+            }
+            CollectionFormat::Toml => toml::from_str(content).map_err(|e| {
+                let (line, column) = e
+                    .span()
+                    .map(|span| line_and_column(content, span.start))
+                    .unzip();
+                parse_error(line, column, e.message().to_string())
+            }),
+            CollectionFormat::Json => serde_json::from_str(content)
+                .map_err(|e| parse_error(Some(e.line()), Some(e.column()), e.to_string())),
+            CollectionFormat::Json5 => json5::from_str(content).map_err(|e| match e {
+                json5::Error::Message { msg, location } => parse_error(
+                    location.as_ref().map(|l| l.line),
+                    location.as_ref().map(|l| l.column),
+                    msg,
+                ),
+            }),
+        }
+    }
+}
+
+/// One based line and column of a byte offset.
+fn line_and_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.chars().rev().take_while(|c| *c != '\n').count() + 1;
+    (line, column)
+}
@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use crate::cmd::{error::ConfigError, input};
+
+use super::{
+    APIClient, APIConfig, APIDefaults, APIEndpoint, APIEndpointDefinition, APIEndpointType,
+    APIMethod,
+};
+
+impl APIConfig {
+    /// Turns the endpoint definitions into endpoints by applying `extends` and
+    /// the API `defaults`. Endpoints that cannot be resolved are left out and
+    /// reported.
+    pub(super) fn resolve_endpoints(&mut self, api: &str) -> Vec<ConfigError> {
+        let mut errors = Vec::new();
+        let defaults = self.defaults_definition();
+        let mut names: Vec<&String> = self.definitions.keys().collect();
+        names.sort();
+        for name in names {
+            let invalid = |reason: String| {
+                ConfigError::InvalidEndpoint(
+                    self.sources.first().cloned().unwrap_or_default(),
+                    api.to_string(),
+                    name.clone(),
+                    reason,
+                )
+            };
+            let resolved = resolve_definition(&self.definitions, name, &mut Vec::new())
+                .map(|definition| definition.inherit(defaults.clone()))
+                .map_err(invalid)
+                .and_then(|definition| definition.into_endpoint().map_err(invalid));
+            match resolved {
+                Ok(endpoint) => {
+                    self.endpoints.insert(name.clone(), endpoint);
+                }
+                Err(e) => errors.push(e),
+            }
+        }
+        errors
+    }
+
+    fn defaults_definition(&self) -> APIEndpointDefinition {
+        self.defaults
+            .as_ref()
+            .map(APIEndpointDefinition::from)
+            .unwrap_or_default()
+    }
+
+    /// Checks that a definition would resolve once added to the API under
+    /// `name`, as a copied endpoint is.
+    pub fn check_definition(
+        &self,
+        name: &str,
+        definition: &APIEndpointDefinition,
+    ) -> Result<(), String> {
+        let mut definitions = self.definitions.clone();
+        definitions.insert(name.to_string(), definition.clone());
+        resolve_definition(&definitions, name, &mut Vec::new())
+            .map(|definition| definition.inherit(self.defaults_definition()))
+            .and_then(|definition| definition.into_endpoint())
+            .map(|_| ())
+    }
+}
+
+fn resolve_definition(
+    definitions: &HashMap<String, APIEndpointDefinition>,
+    name: &str,
+    chain: &mut Vec<String>,
+) -> Result<APIEndpointDefinition, String> {
+    if chain.iter().any(|c| c == name) {
+        chain.push(name.to_string());
+        return Err(format!("inheritance cycle {}", chain.join(" -> ")));
+    }
+    let definition = &definitions[name];
+    let parent = match &definition.extends {
+        None => return Ok(definition.clone()),
+        Some(parent) if !definitions.contains_key(parent) => {
+            return Err(format!("extends unknown endpoint {}", parent))
+        }
+        Some(parent) => parent,
+    };
+    chain.push(name.to_string());
+    let resolved_parent = resolve_definition(definitions, parent, chain)?;
+    chain.pop();
+    Ok(definition.clone().inherit(resolved_parent))
+}
+
+fn merge_maps<V>(
+    parent: Option<HashMap<String, V>>,
+    child: Option<HashMap<String, V>>,
+) -> Option<HashMap<String, V>> {
+    match (parent, child) {
+        (Some(mut parent), Some(child)) => {
+            parent.extend(child);
+            Some(parent)
+        }
+        (parent, child) => child.or(parent),
+    }
+}
+
+impl APIEndpointDefinition {
+    /// Fills what this definition leaves out with the parent's values, maps
+    /// such as headers are merged key by key.
+    fn inherit(self, parent: APIEndpointDefinition) -> APIEndpointDefinition {
+        let client = match (parent.client, self.client) {
+            (Some(parent), Some(child)) => Some(APIClient {
+                timeout: child.timeout.or(parent.timeout),
+                insecure: child.insecure.or(parent.insecure),
+                follow_redirects: child.follow_redirects.or(parent.follow_redirects),
+            }),
+            (parent, child) => child.or(parent),
+        };
+        APIEndpointDefinition {
+            extends: None,
+            method: self.method.or(parent.method),
+            url: self.url.or(parent.url),
+            headers: merge_maps(parent.headers, self.headers),
+            query: merge_maps(parent.query, self.query),
+            body: self.body.or(parent.body),
+            auth: self.auth.or(parent.auth),
+            client,
+            inputs: merge_maps(parent.inputs, self.inputs),
+            output: self.output.or(parent.output),
+            expect_status: self.expect_status.or(parent.expect_status),
+            stream: self.stream.or(parent.stream),
+            endpoint_type: self.endpoint_type.or(parent.endpoint_type),
+            graphql: self.graphql.or(parent.graphql),
+            grpc: self.grpc.or(parent.grpc),
+            capture: merge_maps(parent.capture, self.capture),
+        }
+    }
+
+    fn into_endpoint(self) -> Result<APIEndpoint, String> {
+        let endpoint_type = self.endpoint_type.unwrap_or_default();
+        let method = match (self.method, &endpoint_type) {
+            (Some(method), _) => method,
+            (None, APIEndpointType::GraphQL | APIEndpointType::Grpc) => APIMethod::POST,
+            (None, APIEndpointType::Http) => return Err("missing field `method`".to_string()),
+        };
+        match endpoint_type {
+            APIEndpointType::GraphQL if self.graphql.is_none() => {
+                return Err("missing field `graphql` for a graphql endpoint".to_string())
+            }
+            APIEndpointType::Grpc if self.grpc.is_none() => {
+                return Err("missing field `grpc` for a grpc endpoint".to_string())
+            }
+            _ => (),
+        }
+        let mut inputs: Vec<_> = self.inputs.iter().flatten().collect();
+        inputs.sort_by_key(|(name, _)| *name);
+        for (name, input) in inputs {
+            input::check_declaration(name, input)?;
+        }
+        Ok(APIEndpoint {
+            method,
+            url: self.url.ok_or("missing field `url`")?,
+            headers: self.headers,
+            query: self.query,
+            body: self.body,
+            auth: self.auth,
+            client: self.client,
+            inputs: self.inputs,
+            output: self.output,
+            expect_status: self.expect_status,
+            stream: self.stream,
+            endpoint_type,
+            graphql: self.graphql,
+            grpc: self.grpc,
+            capture: self.capture,
+        })
+    }
+}
+
+impl From<&APIDefaults> for APIEndpointDefinition {
+    fn from(defaults: &APIDefaults) -> Self {
+        APIEndpointDefinition {
+            headers: defaults.headers.clone(),
+            query: defaults.query.clone(),
+            auth: defaults.auth.clone(),
+            client: defaults.client.clone(),
+            ..Default::default()
+        }
+    }
+}
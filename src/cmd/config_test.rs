@@ -0,0 +1,300 @@
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::cmd::config::{
+        collection_schema, config_schema, find_project, APIBodyType, APIMethod, Config,
+    };
+    use crate::cmd::error::ConfigError;
+
+    pub fn collection_directory(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("rbm-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).unwrap();
+        for (file, content) in files {
+            let path = directory.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        directory
+    }
+
+    pub fn config_for(directory: PathBuf) -> Config {
+        Config {
+            config_directory: directory,
+            api_collection_directory: vec![".".into()],
+            global_context: None,
+            namespace_from_path: false,
+            project: None,
+        }
+    }
+
+    #[test]
+    fn read_apis_reports_broken_files_test() {
+        let directory = collection_directory(
+            "broken",
+            &[
+                ("valid.yml", "valid:\n  endpoints:\n    get:\n      method: GET\n      url: http://localhost\n"),
+                ("broken.yml", "broken:\n  endpoints:\n    get:\n      method: FETCH\n      url: http://localhost\n"),
+            ],
+        );
+        let (apis, errors) = config_for(directory).read_apis();
+        assert!(apis.contains_key("valid"));
+        assert!(!apis.contains_key("broken"));
+        assert_eq!(errors.len(), 1);
+        match &errors[0] {
+            ConfigError::Parse(path, Some(line), Some(_), _) => {
+                assert!(path.ends_with("broken.yml"));
+                assert_eq!(*line, 4);
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+
+    #[test]
+    fn read_apis_detects_duplicates_and_merges_test() {
+        let endpoint = |name: &str| {
+            format!(
+                "    {}:\n      method: GET\n      url: http://localhost\n",
+                name
+            )
+        };
+        let directory = collection_directory(
+            "duplicates",
+            &[
+                ("a.yml", &format!("dup:\n  endpoints:\n{}", endpoint("one"))),
+                ("b.yml", &format!("dup:\n  endpoints:\n{}", endpoint("two"))),
+                ("c.yml", &format!("merged:\n  merge: true\n  context:\n    local:\n      url: a\n  endpoints:\n{}", endpoint("one"))),
+                ("d.yml", &format!("merged:\n  merge: true\n  context:\n    local:\n      url: b\n      auth: c\n  endpoints:\n{}{}", endpoint("one"), endpoint("two"))),
+            ],
+        );
+        let (apis, errors) = config_for(directory).read_apis();
+        let merged = &apis["merged"];
+        assert!(merged.contains_endpoint("one") && merged.contains_endpoint("two"));
+        assert_eq!(merged.get_api_context("local").unwrap()["url"], "a");
+        assert_eq!(merged.get_api_context("local").unwrap()["auth"], "c");
+        assert_eq!(merged.get_sources().len(), 2);
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(&errors[0], ConfigError::DuplicateAPI(api, a, b)
+            if api == "dup" && a.ends_with("a.yml") && b.ends_with("b.yml")));
+        let conflicts: Vec<&String> = errors[1..]
+            .iter()
+            .map(|e| match e {
+                ConfigError::MergeConflict(_, what, _, _) => what,
+                other => panic!("unexpected error {:?}", other),
+            })
+            .collect();
+        assert!(conflicts.contains(&&"endpoint one".to_string()));
+        assert!(conflicts.contains(&&"context local key url".to_string()));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn read_apis_walks_nested_directories_test() {
+        let api = |name: &str| {
+            format!(
+                "{}:\n  endpoints:\n    get:\n      method: GET\n      url: http://localhost\n",
+                name
+            )
+        };
+        let directory = collection_directory(
+            "nested",
+            &[
+                ("root.yml", &api("root")),
+                ("payments/refunds.yml", &api("refunds")),
+                ("payments/drafts/wip.yaml", &api("wip")),
+                ("payments/.rbmignore", "# work in progress\ndrafts/\n"),
+                ("shared/users.yml", &api("users")),
+                ("notes.txt", "not a collection"),
+            ],
+        );
+        std::os::unix::fs::symlink(directory.join("shared"), directory.join("payments/shared"))
+            .unwrap();
+        std::os::unix::fs::symlink(&directory, directory.join("shared/loop")).unwrap();
+
+        let mut config = config_for(directory);
+        let (apis, errors) = config.read_apis();
+        assert!(errors.is_empty(), "{:?}", errors);
+        let mut names: Vec<&String> = apis.keys().collect();
+        names.sort();
+        assert_eq!(names, ["refunds", "root", "users"]);
+
+        config.namespace_from_path = true;
+        let (apis, _) = config.read_apis();
+        let mut names: Vec<&String> = apis.keys().collect();
+        names.sort();
+        assert_eq!(
+            names,
+            [
+                "payments.refunds",
+                "payments.shared.users",
+                "root",
+                "shared.users"
+            ]
+        );
+    }
+
+    #[test]
+    fn project_collections_win_test() {
+        let api = |url: &str| {
+            format!(
+                "shared:\n  endpoints:\n    get:\n      method: GET\n      url: {}\n",
+                url
+            )
+        };
+        let directory = collection_directory(
+            "project",
+            &[
+                ("global/shared.yml", &api("http://global")),
+                ("repo/.rbm/shared.yml", &api("http://project")),
+                ("repo/src/deep/.keep", ""),
+            ],
+        );
+        let mut config = config_for(directory.join("global"));
+        config.project = find_project(&directory.join("repo/src/deep"))
+            .unwrap()
+            .map(Box::new);
+        let (apis, errors) = config.read_apis();
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(
+            apis["shared"].get_api_endpoint("get").unwrap().url,
+            "http://project"
+        );
+    }
+
+    #[test]
+    fn read_apis_resolves_inheritance_and_includes_test() {
+        let directory = collection_directory(
+            "inheritance",
+            &[
+                (
+                    "apis/users.yml",
+                    "include:\n  - ../shared/users-admin.yml\nusers:\n  defaults:\n    headers:\n      Accept: application/json\n      X-Team: users\n    client:\n      timeout: 5\n  endpoints:\n    get:\n      method: GET\n      url: http://localhost/users/{{id}}\n      headers:\n        X-Team: accounts\n    delete:\n      extends: get\n      method: DELETE\n    check:\n      extends: health\n    loop_a:\n      extends: loop_b\n    loop_b:\n      extends: loop_a\n    orphan:\n      extends: missing\n    search:\n      method: GET\n      url: http://localhost/users\n      inputs:\n        q:\n          type: regex\n",
+                ),
+                (
+                    "shared/users-admin.yml",
+                    "users:\n  endpoints:\n    purge:\n      extends: delete\n      url: http://localhost/users\n    health:\n      type: grpc\n      url: http://localhost\n      grpc:\n        method: grpc.health.v1.Health/Check\n        protos: [health.proto]\n",
+                ),
+                ("apis/orders.yml", "include: [orders-admin.yml]\norders:\n  endpoints:\n    get:\n      method: GET\n      url: http://localhost/orders\n"),
+                // Also found by discovery, it is only loaded through the include.
+                ("apis/orders-admin.yml", "orders:\n  endpoints:\n    purge:\n      method: DELETE\n      url: http://localhost/orders\n"),
+                ("apis/a.yml", "include: [b.yml]\n"),
+                ("apis/b.yml", "include: [a.yml]\n"),
+            ],
+        );
+        let (apis, errors) = config_for(directory.join("apis")).read_apis();
+        let users = &apis["users"];
+        let delete = users.get_api_endpoint("delete").unwrap();
+        assert!(matches!(delete.method, APIMethod::DELETE));
+        assert_eq!(delete.url, "http://localhost/users/{{id}}");
+        let headers = delete.headers.as_ref().unwrap();
+        assert_eq!(headers["Accept"], "application/json");
+        assert_eq!(headers["X-Team"], "accounts");
+        assert_eq!(delete.client.as_ref().unwrap().timeout, Some(5));
+        let purge = users.get_api_endpoint("purge").unwrap();
+        assert!(matches!(purge.method, APIMethod::DELETE));
+        assert_eq!(purge.url, "http://localhost/users");
+        // Relative proto paths stay relative to the file defining them.
+        let check = users
+            .get_api_endpoint("check")
+            .unwrap()
+            .grpc
+            .as_ref()
+            .unwrap();
+        assert!(check
+            .source
+            .as_ref()
+            .unwrap()
+            .ends_with("shared/users-admin.yml"));
+        assert!(
+            apis["orders"].contains_endpoint("get") && apis["orders"].contains_endpoint("purge")
+        );
+
+        let mut messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        messages.sort();
+        assert_eq!(messages.len(), 6, "{:?}", messages);
+        assert!(messages[0].contains("Include cycle"));
+        assert!(messages[1].contains("Include cycle"));
+        assert!(
+            messages[2].contains("users.loop_a")
+                && messages[2].contains("loop_a -> loop_b -> loop_a")
+        );
+        assert!(messages[3].contains("users.loop_b"));
+        assert!(
+            messages[4].contains("users.orphan")
+                && messages[4].contains("extends unknown endpoint missing")
+        );
+        assert!(
+            messages[5].contains("users.search")
+                && messages[5].contains("input q: missing field `pattern`")
+        );
+    }
+
+    #[test]
+    fn read_apis_supports_toml_and_json_test() {
+        let directory = collection_directory(
+            "formats",
+            &[
+                ("toml.toml", "[toml.endpoints.post]\nmethod = \"POST\"\nurl = \"http://localhost\"\nbody = \"{{payload}}\"\n"),
+                ("json.json", r#"{"json": {"endpoints": {"post": {"method": "POST", "url": "http://localhost", "body": {"type": "file", "content": "payload.json"}}}}}"#),
+                ("json5.json5", "{json5: {endpoints: {get: {method: 'GET', url: 'http://localhost', }}}}"),
+                ("broken.toml", "[broken]\nendpoints = 1\n"),
+                // Payloads kept next to the collections are ignored.
+                (".rbmignore", "payload.json\npayloads/\n"),
+                ("payload.json", r#"{"name": "rex"}"#),
+                ("payloads/settings.toml", "name = \"rex\"\n"),
+            ],
+        );
+        let (apis, errors) = config_for(directory).read_apis();
+        let toml_body = apis["toml"]
+            .get_api_endpoint("post")
+            .unwrap()
+            .body
+            .as_ref()
+            .unwrap();
+        assert!(matches!(toml_body.api_body_type, APIBodyType::STRING));
+        assert_eq!(toml_body.content, "{{payload}}");
+        let json_body = apis["json"]
+            .get_api_endpoint("post")
+            .unwrap()
+            .body
+            .as_ref()
+            .unwrap();
+        assert!(matches!(json_body.api_body_type, APIBodyType::FILE));
+        assert!(apis["json5"].contains_endpoint("get"));
+        assert_eq!(errors.len(), 1);
+        assert!(
+            matches!(&errors[0], ConfigError::Parse(path, Some(2), Some(_), _) if path.ends_with("broken.toml"))
+        );
+    }
+
+    #[test]
+    fn collection_schema_test() {
+        let schema = serde_json::to_value(collection_schema()).unwrap();
+        assert!(schema["properties"]["include"].is_object());
+        assert_eq!(
+            schema["additionalProperties"]["$ref"],
+            "#/definitions/APIConfig"
+        );
+        let endpoint = &schema["definitions"]["APIEndpointDefinition"]["properties"];
+        for field in [
+            "extends", "method", "url", "headers", "query", "body", "auth", "client", "inputs",
+        ] {
+            assert!(endpoint[field].is_object(), "missing {}", field);
+        }
+        assert_eq!(endpoint["body"]["anyOf"].as_array().unwrap().len(), 2);
+        let body_types = schema["definitions"]["APIBody"]["properties"]["type"]["enum"]
+            .as_array()
+            .unwrap();
+        assert_eq!(body_types, &["FILE", "STRING", "file", "string"]);
+        for body_type in body_types {
+            assert!(
+                serde_json::from_value::<APIBodyType>(body_type.clone()).is_ok(),
+                "{}",
+                body_type
+            );
+        }
+        let config = serde_json::to_value(config_schema()).unwrap();
+        assert!(config["properties"]["api-collection-directories"].is_object());
+    }
+}
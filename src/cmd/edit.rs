@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use super::{
+    config::{
+        editor::{scalar, YamlDocument},
+        APIConfig,
+    },
+    error::ExecutorError,
+};
+
+/// Sets context values, creating the context when it does not exist yet.
+pub fn set_context(
+    api: &str,
+    api_config: &APIConfig,
+    context: &str,
+    values: &[(String, String)],
+) -> Result<(), ExecutorError> {
+    let (mut document, key) = YamlDocument::open_api(api, api_config, &["context", context])?;
+    for (name, value) in values {
+        document.set(&[&key, "context", context], name, &scalar(value))?;
+    }
+    document.save()?;
+    println!(
+        "Updated context {}.{} in {}",
+        api,
+        context,
+        document.path().display()
+    );
+    Ok(())
+}
+
+/// Removes a context, or only some of its values when keys are given.
+pub fn remove_context(
+    api: &str,
+    api_config: &APIConfig,
+    context: &str,
+    keys: &[String],
+) -> Result<(), ExecutorError> {
+    let (mut document, key) = YamlDocument::open_api(api, api_config, &["context", context])?;
+    match keys.is_empty() {
+        true => document.remove(&[&key, "context", context])?,
+        false => {
+            for name in keys {
+                document.remove(&[&key, "context", context, name])?;
+            }
+        }
+    }
+    document.save()?;
+    println!(
+        "Updated context {}.{} in {}",
+        api,
+        context,
+        document.path().display()
+    );
+    Ok(())
+}
+
+/// Fails while other endpoints of the API extend the one about to go away.
+fn check_not_extended(api: &str, api_config: &APIConfig, name: &str) -> Result<(), ExecutorError> {
+    let extending = api_config.get_extending(name);
+    match extending.is_empty() {
+        true => Ok(()),
+        false => {
+            let names: Vec<&str> = extending.iter().map(|n| n.as_str()).collect();
+            Err(ExecutorError::EndpointExtended(
+                api.to_string(),
+                name.to_string(),
+                names.join(", "),
+            ))
+        }
+    }
+}
+
+pub fn remove_endpoint(api: &str, api_config: &APIConfig, name: &str) -> Result<(), ExecutorError> {
+    check_not_extended(api, api_config, name)?;
+    let (mut document, key) = YamlDocument::open_api(api, api_config, &["endpoints", name])?;
+    document.remove(&[&key, "endpoints", name])?;
+    document.save()?;
+    println!(
+        "Removed endpoint {}.{} from {}",
+        api,
+        name,
+        document.path().display()
+    );
+    Ok(())
+}
+
+/// Copies an endpoint under a new name, to another API when `to_api` is
+/// given, and removes the original unless `keep` is set.
+pub fn copy_endpoint(
+    apis: &HashMap<String, APIConfig>,
+    api: &str,
+    name: &str,
+    new_name: &str,
+    to_api: &Option<String>,
+    keep: bool,
+) -> Result<(), ExecutorError> {
+    let target_api = to_api.as_deref().unwrap_or(api);
+    if apis[target_api].contains_endpoint(new_name) {
+        return Err(ExecutorError::EndpointAlreadyExists(
+            target_api.to_string(),
+            new_name.to_string(),
+        ));
+    }
+    if !keep {
+        check_not_extended(api, &apis[api], name)?;
+    }
+    // The snippet is copied as written, its `extends` must resolve in the
+    // target API too.
+    if let Some(definition) = apis[api].get_definition(name) {
+        apis[target_api]
+            .check_definition(new_name, definition)
+            .map_err(|reason| {
+                ExecutorError::CannotCopyEndpoint(
+                    api.to_string(),
+                    name.to_string(),
+                    target_api.to_string(),
+                    reason,
+                )
+            })?;
+    }
+    let (mut source, key) = YamlDocument::open_api(api, &apis[api], &["endpoints", name])?;
+    if !keep && target_api == api {
+        source.rename(&[&key, "endpoints", name], new_name)?;
+        source.save()?;
+        println!(
+            "Renamed endpoint {}.{} to {} in {}",
+            api,
+            name,
+            new_name,
+            source.path().display()
+        );
+        return Ok(());
+    }
+    let snippet = source.snippet(&[&key, "endpoints", name])?;
+    let (first, rest) = snippet.split_once('\n').unwrap_or((&snippet, ""));
+    let value = first.split_once(':').map(|(_, v)| v.trim()).unwrap_or("");
+    let key_line = format!("{}: {}", scalar(new_name), value);
+    let snippet = format!("{}\n{}\n", key_line.trim_end(), rest);
+    // The copy is written before the original is removed, the files are read
+    // again in between as both endpoints may live in the same one.
+    let (mut target, target_key) =
+        YamlDocument::open_api(target_api, &apis[target_api], &["endpoints"])?;
+    if target.contains(&[&target_key, "endpoints", new_name]) {
+        return Err(ExecutorError::EndpointAlreadyExists(
+            target_api.to_string(),
+            new_name.to_string(),
+        ));
+    }
+    target.insert(&[&target_key, "endpoints"], &snippet)?;
+    target.save()?;
+    if !keep {
+        let (mut source, key) = YamlDocument::open_api(api, &apis[api], &["endpoints", name])?;
+        source.remove(&[&key, "endpoints", name])?;
+        source.save()?;
+    }
+    println!(
+        "{} endpoint {}.{} to {}.{} in {}",
+        if keep { "Copied" } else { "Moved" },
+        api,
+        name,
+        target_api,
+        new_name,
+        target.path().display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "./edit_test.rs"]
+mod edit_test;
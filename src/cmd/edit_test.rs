@@ -0,0 +1,132 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::{Path, PathBuf};
+
+    use crate::cmd::config::{read_config_or_create_default, APIConfig};
+    use crate::cmd::edit::{copy_endpoint, remove_context, remove_endpoint, set_context};
+    use crate::cmd::error::ExecutorError;
+
+    const PETS: &str = "pets:\n  context:\n    local:\n      url: localhost # dev\n  endpoints:\n    get:\n      method: GET\n      url: \"{{url}}/pets/1\"\n    delete:\n      extends: get\n      method: DELETE\n    list:\n      method: GET\n      url: \"{{url}}/pets\"\n";
+
+    const USERS: &str =
+        "users:\n  endpoints:\n    me:\n      method: GET\n      url: http://localhost/me\n";
+
+    fn directory(name: &str) -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("rbm-edit-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(directory.join("apis")).unwrap();
+        std::fs::write(
+            directory.join("config"),
+            "api-collection-directories: [apis]\n",
+        )
+        .unwrap();
+        std::fs::write(directory.join("apis/pets.yml"), PETS).unwrap();
+        std::fs::write(directory.join("apis/users.yml"), USERS).unwrap();
+        directory
+    }
+
+    fn read_apis(directory: &Path) -> HashMap<String, APIConfig> {
+        let config = read_config_or_create_default(&Some(directory.join("config"))).unwrap();
+        let (apis, errors) = config.read_apis();
+        assert!(errors.is_empty(), "{:?}", errors);
+        apis
+    }
+
+    fn pets(directory: &Path) -> String {
+        std::fs::read_to_string(directory.join("apis/pets.yml")).unwrap()
+    }
+
+    #[test]
+    fn extended_endpoints_stay_test() {
+        let directory = directory("extended");
+        let apis = read_apis(&directory);
+        assert!(matches!(
+            copy_endpoint(&apis, "pets", "get", "fetch", &None, false),
+            Err(ExecutorError::EndpointExtended(api, name, by)) if api == "pets" && name == "get" && by == "delete"
+        ));
+        assert!(matches!(
+            remove_endpoint("pets", &apis["pets"], "get"),
+            Err(ExecutorError::EndpointExtended(_, _, _))
+        ));
+        assert_eq!(pets(&directory), PETS);
+
+        copy_endpoint(&apis, "pets", "get", "fetch", &None, true).unwrap();
+        let apis = read_apis(&directory);
+        assert_eq!(
+            apis["pets"].get_api_endpoint("fetch").unwrap().url,
+            "{{url}}/pets/1"
+        );
+        copy_endpoint(&apis, "pets", "list", "all", &None, false).unwrap();
+        let apis = read_apis(&directory);
+        assert!(apis["pets"].contains_endpoint("all") && !apis["pets"].contains_endpoint("list"));
+        remove_endpoint("pets", &apis["pets"], "delete").unwrap();
+        remove_endpoint("pets", &read_apis(&directory)["pets"], "get").unwrap();
+        assert!(!pets(&directory).contains("get:"));
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn context_edits_keep_comments_test() {
+        let directory = directory("context");
+        let apis = read_apis(&directory);
+        let values = [
+            ("url".to_string(), "remote".to_string()),
+            ("token".to_string(), "{{x}}".to_string()),
+        ];
+        set_context("pets", &apis["pets"], "local", &values).unwrap();
+        set_context("pets", &apis["pets"], "prod", &values[..1]).unwrap();
+        let content = pets(&directory);
+        assert!(
+            content.contains("      url: remote # dev\n      token: '{{x}}'\n"),
+            "{}",
+            content
+        );
+        assert!(
+            content.contains("    prod:\n      url: remote\n"),
+            "{}",
+            content
+        );
+
+        let apis = read_apis(&directory);
+        remove_context("pets", &apis["pets"], "local", &["token".to_string()]).unwrap();
+        remove_context("pets", &apis["pets"], "prod", &[]).unwrap();
+        let apis = read_apis(&directory);
+        let contexts = apis["pets"].get_api_contexts().unwrap();
+        assert_eq!(contexts.len(), 1);
+        assert_eq!(
+            contexts["local"],
+            HashMap::from([("url".to_string(), "remote".to_string())])
+        );
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn copies_to_another_api_resolve_test() {
+        let directory = directory("to-api");
+        let apis = read_apis(&directory);
+        let to_users = Some("users".to_string());
+        for keep in [false, true] {
+            assert!(matches!(
+                copy_endpoint(&apis, "pets", "delete", "remove", &to_users, keep),
+                Err(ExecutorError::CannotCopyEndpoint(_, name, to, reason))
+                    if name == "delete" && to == "users" && reason == "extends unknown endpoint get"
+            ));
+        }
+        assert_eq!(pets(&directory), PETS);
+        assert_eq!(
+            std::fs::read_to_string(directory.join("apis/users.yml")).unwrap(),
+            USERS
+        );
+
+        copy_endpoint(&apis, "pets", "list", "pets", &to_users, false).unwrap();
+        let apis = read_apis(&directory);
+        assert_eq!(
+            apis["users"].get_api_endpoint("pets").unwrap().url,
+            "{{url}}/pets"
+        );
+        assert!(!apis["pets"].contains_endpoint("list"));
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -1,11 +1,40 @@
+use std::path::{Path, PathBuf};
+
 use thiserror::Error;
 
+/// Exit code used when the configuration or a collection file is invalid,
+/// following `EX_CONFIG` from sysexits.h.
+pub const CONFIG_ERROR_EXIT_CODE: i32 = 78;
+/// Exit code used when a request could not be sent or its response could not
+/// be read, following `EX_UNAVAILABLE`.
+pub const TRANSPORT_ERROR_EXIT_CODE: i32 = 69;
+/// Exit code used when a template cannot be rendered, following `EX_DATAERR`.
+pub const TEMPLATE_ERROR_EXIT_CODE: i32 = 65;
+/// Exit code used for inputs that are unknown, missing or invalid, following
+/// `EX_USAGE`.
+pub const INPUT_ERROR_EXIT_CODE: i32 = 64;
+/// Exit code used when the response status is not the expected one, the same
+/// as `curl --fail`, a GraphQL response has errors or a gRPC call fails.
+pub const STATUS_EXIT_CODE: i32 = 22;
+/// Exit code used when Ctrl-C stops a request, the shell convention for SIGINT.
+pub const INTERRUPTED_EXIT_CODE: i32 = 130;
+/// Exit code used for every other failure.
+pub const ERROR_EXIT_CODE: i32 = 1;
+
 #[derive(Error, Debug)]
 pub enum ExecutorError {
     #[error("API not found: {0}")]
     APINotFound(String),
     #[error("Endpoint not found: {0}")]
     EndpointNotFound(String),
+    #[error("Context not found: {0}")]
+    ContextNotFound(String),
+    #[error("Unknown input for endpoint {0}: {1}")]
+    UnknownInput(String, String),
+    #[error("Missing required input for endpoint {0}: {1}")]
+    MissingInput(String, String),
+    #[error("Invalid value for input {0}: {1}")]
+    InvalidInput(String, String),
     #[error("HTTP Request error: {0}")]
     HTTPRequestError(String),
     #[error("Failed to parse header: {0}. Err: {1}")]
@@ -14,4 +43,158 @@ pub enum ExecutorError {
     FailedToReadBody(String),
     #[error("Failed to write body into stdout: {0}")]
     FailedToPrintBody(String),
+    #[error("Failed to write {0}: {1}")]
+    FailedToWriteFile(String, String),
+    #[error("Failed to resolve {2} of {0}.{1}: {3}")]
+    FailedToResolveTemplate(String, String, String, ResolverError),
+    #[error("Invalid header name in {0}.{1}: {2}. Err: {3}")]
+    InvalidHeaderName(String, String, String, String),
+    #[error("Invalid value for header {2} in {0}.{1}. Err: {3}")]
+    InvalidHeaderValue(String, String, String, String),
+    #[error("Failed to open body file {0}: {1}")]
+    FailedToOpenBodyFile(String, String),
+    #[error("API {0} already exists")]
+    APIAlreadyExists(String),
+    #[error("Endpoint {0}.{1} already exists")]
+    EndpointAlreadyExists(String, String),
+    #[error("Endpoint {0}.{1} is extended by {2}, change their `extends` first")]
+    EndpointExtended(String, String, String),
+    #[error("Cannot copy endpoint {0}.{1} to {2}: {3}")]
+    CannotCopyEndpoint(String, String, String, String),
+    #[error("No collection directory is configured, pass the file to write to")]
+    NoCollectionDirectory,
+    #[error("Invalid filter `{0}`: {1}")]
+    InvalidFilter(String, String),
+    #[error("Filter `{0}` failed: {1}")]
+    FilterFailed(String, String),
+    #[error("Cannot filter a body that is not JSON: {0}")]
+    BodyNotJson(String),
+    #[error("Interrupted")]
+    Interrupted,
+    #[error("Could not set the Ctrl-C handler: {0}")]
+    FailedToWatchInterrupt(String),
+    #[error("Unexpected HTTP status {0}, expected {1}")]
+    UnexpectedStatus(u16, String),
+    #[error("Invalid GraphQL variables in {0}.{1}: {2}")]
+    InvalidGraphQLVariables(String, String, String),
+    #[error("{0}.{1} is not a graphql endpoint")]
+    NotGraphQL(String, String),
+    #[error("The GraphQL response has {0} error(s)")]
+    GraphQLErrors(usize),
+    #[error("Could not load the gRPC descriptors: {0}")]
+    GrpcDescriptors(String),
+    #[error("gRPC method {0} not found: {1}")]
+    GrpcMethodNotFound(String, String),
+    #[error("Invalid gRPC request message: {0}")]
+    InvalidGrpcMessage(String),
+    #[error("gRPC call failed with {0}: {1}")]
+    GrpcStatus(String, String),
+    #[error("Flow not found: {0}")]
+    FlowNotFound(String),
+    #[error("Invalid step {1} of flow {0}: {2}")]
+    InvalidFlowStep(String, String, String),
+    #[error("Failed to capture {0}: {1}")]
+    CaptureFailed(String, String),
+    #[error("Cannot capture from {0}, captures need the whole response")]
+    CaptureNotSupported(String),
+    #[error("{0} is not supported for {1}")]
+    OptionNotSupported(String, String),
+    #[error("Could not read the session {0}: {1}")]
+    FailedToReadSession(String, String),
+    #[error("Validation failed with {0} issue(s)")]
+    ValidationFailed(usize),
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+}
+
+impl ExecutorError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ExecutorError::Config(_) => CONFIG_ERROR_EXIT_CODE,
+            ExecutorError::HTTPRequestError(_)
+            | ExecutorError::FailedToParseHeader(_, _)
+            | ExecutorError::FailedToReadBody(_) => TRANSPORT_ERROR_EXIT_CODE,
+            ExecutorError::FailedToResolveTemplate(_, _, _, _)
+            | ExecutorError::InvalidGraphQLVariables(_, _, _)
+            | ExecutorError::InvalidGrpcMessage(_) => TEMPLATE_ERROR_EXIT_CODE,
+            ExecutorError::UnknownInput(_, _)
+            | ExecutorError::MissingInput(_, _)
+            | ExecutorError::InvalidInput(_, _) => INPUT_ERROR_EXIT_CODE,
+            ExecutorError::UnexpectedStatus(_, _)
+            | ExecutorError::GraphQLErrors(_)
+            | ExecutorError::GrpcStatus(_, _) => STATUS_EXIT_CODE,
+            ExecutorError::Interrupted => INTERRUPTED_EXIT_CODE,
+            _ => ERROR_EXIT_CODE,
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Could not find the default config directory")]
+    NoConfigDirectory,
+    #[error("Could not read {}: {1}", .0.display())]
+    Io(PathBuf, String),
+    #[error("Could not write {}: {1}", .0.display())]
+    Write(PathBuf, String),
+    #[error("Could not parse {}{}: {3}", .0.display(), location(.1, .2))]
+    Parse(PathBuf, Option<usize>, Option<usize>, String),
+    #[error("Configured api collection directory {} is not a folder", .0.display())]
+    NotADirectory(PathBuf),
+    #[error(
+        "API {0} is defined in both {} and {}, rename one or set `merge: true` in both",
+        .1.display(),
+        .2.display()
+    )]
+    DuplicateAPI(String, PathBuf, PathBuf),
+    #[error(
+        "Conflicting {1} while merging API {0} from {} and {}",
+        .2.display(),
+        .3.display()
+    )]
+    MergeConflict(String, String, PathBuf, PathBuf),
+    #[error("Invalid endpoint {1}.{2} in {}: {3}", .0.display())]
+    InvalidEndpoint(PathBuf, String, String, String),
+    #[error("Include cycle: {0}")]
+    IncludeCycle(String),
+    #[error("Could not edit {}: {1}", .0.display())]
+    Edit(PathBuf, String),
+    #[error("Failed to load the API collections, {} error(s):{}", .0.len(), list(.0))]
+    Collections(Vec<ConfigError>),
+}
+
+impl ConfigError {
+    pub fn from_yaml(path: &Path, err: &serde_yaml::Error) -> Self {
+        let location = err.location();
+        ConfigError::Parse(
+            path.to_path_buf(),
+            location.as_ref().map(|l| l.line()),
+            location.as_ref().map(|l| l.column()),
+            err.to_string(),
+        )
+    }
 }
+
+fn location(line: &Option<usize>, column: &Option<usize>) -> String {
+    match (line, column) {
+        (Some(line), Some(column)) => format!(":{}:{}", line, column),
+        (Some(line), None) => format!(":{}", line),
+        _ => String::new(),
+    }
+}
+
+fn list(errors: &[ConfigError]) -> String {
+    errors.iter().map(|e| format!("\n  {}", e)).collect()
+}
+
+#[derive(Error, Debug)]
+pub enum ResolverError {
+    #[error("syntax error at line {0}, column {1}: {2}")]
+    Syntax(usize, usize, String),
+    #[error("{0}")]
+    Render(String),
+}
+
+#[cfg(test)]
+#[path = "./error_test.rs"]
+mod error_test;
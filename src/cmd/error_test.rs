@@ -0,0 +1,43 @@
+#[cfg(test)]
+mod tests {
+    use crate::cmd::error::{ConfigError, ExecutorError, ResolverError};
+
+    #[test]
+    fn exit_code_test() {
+        let cases = [
+            (ExecutorError::UnexpectedStatus(404, "200".to_string()), 22),
+            (ExecutorError::GraphQLErrors(1), 22),
+            (
+                ExecutorError::GrpcStatus("NotFound".to_string(), "missing".to_string()),
+                22,
+            ),
+            (
+                ExecutorError::MissingInput("get".to_string(), "id".to_string()),
+                64,
+            ),
+            (
+                ExecutorError::InvalidInput("id".to_string(), "not an int".to_string()),
+                64,
+            ),
+            (
+                ExecutorError::FailedToResolveTemplate(
+                    "pets".to_string(),
+                    "get".to_string(),
+                    "url".to_string(),
+                    ResolverError::Render("unknown variable".to_string()),
+                ),
+                65,
+            ),
+            (ExecutorError::InvalidGrpcMessage("bad".to_string()), 65),
+            (ExecutorError::HTTPRequestError("refused".to_string()), 69),
+            (ExecutorError::FailedToReadBody("reset".to_string()), 69),
+            (ExecutorError::Config(ConfigError::NoConfigDirectory), 78),
+            (ExecutorError::Interrupted, 130),
+            (ExecutorError::APINotFound("pets".to_string()), 1),
+            (ExecutorError::ValidationFailed(2), 1),
+        ];
+        for (error, code) in cases {
+            assert_eq!(error.exit_code(), code, "{}", error);
+        }
+    }
+}
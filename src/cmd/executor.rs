@@ -1,34 +1,326 @@
-use std::{collections::HashMap, fs::File, path::PathBuf, str::FromStr};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::Read,
+    path::PathBuf,
+    str::FromStr,
+    time::{Duration, Instant},
+};
 
+use base64::{engine::general_purpose::STANDARD, Engine as _};
 use reqwest::{
     self,
-    blocking::{Body, RequestBuilder, Response},
-    header::{HeaderMap, HeaderName, HeaderValue},
+    blocking::{Body, Client, Request, RequestBuilder, Response},
+    header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, CONTENT_TYPE},
+    redirect::Policy,
 };
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+use tungstenite::client::IntoClientRequest;
 
 use super::{
-    config::{self, APIBody, APIConfig, AuthEndpoint},
+    capture::Session,
+    config::{
+        self, APIBody, APIClient, APIConfig, APIEndpointType, APIGraphQL, APIStream, AuthEndpoint,
+    },
     error::ExecutorError,
+    graphql::Operation,
+    grpc,
     resolver::{self, Resolver},
+    websocket::WebSocket,
 };
 
-const CONTEXT_KEY: &str = "context";
+pub const CONTEXT_KEY: &str = "context";
+
+/// The content type of Server-Sent Events.
+pub const EVENT_STREAM: &str = "text/event-stream";
+
+const MASK: &str = "***";
 
+#[derive(Serialize)]
 pub struct Header {
     pub key: String,
     pub value: String,
 }
 
+pub fn content_type(headers: &[Header]) -> Option<&str> {
+    headers
+        .iter()
+        .find(|h| h.key.eq_ignore_ascii_case("content-type"))
+        .map(|h| h.value.as_str())
+}
+
+#[derive(Serialize)]
 pub struct HttpResponse {
     pub status: u16,
     pub version: String,
+    /// Printed with the cookies and other credentials masked.
+    #[serde(serialize_with = "serialize_masked_headers")]
     pub headers: Vec<Header>,
+    #[serde(serialize_with = "serialize_body")]
     pub body: Vec<u8>,
 }
 
+/// A response whose body is read as it arrives instead of being buffered.
+pub struct ResponseStream {
+    pub status: u16,
+    pub version: String,
+    pub headers: Vec<Header>,
+    body: Response,
+    started: Instant,
+    headers_ms: u64,
+}
+
+impl ResponseStream {
+    fn new(response: Response, started: Instant) -> Result<ResponseStream, ExecutorError> {
+        let headers_ms = millis(started.elapsed());
+        let status = response.status().as_u16();
+        let mut headers: Vec<Header> = Vec::with_capacity(response.headers().capacity());
+        for (hk, hv) in response.headers().iter() {
+            let key = hk.to_string();
+            let value = String::from_utf8(hv.as_bytes().into())
+                .map_err(|e| ExecutorError::FailedToParseHeader(key.clone(), e.to_string()))?;
+            headers.push(Header { key, value })
+        }
+        let version = format!("{:?}", response.version());
+        Result::Ok(ResponseStream {
+            status,
+            version,
+            headers,
+            body: response,
+            started,
+            headers_ms,
+        })
+    }
+
+    /// Reads the whole body and pairs it with the request that was sent.
+    pub fn into_exchange(self, request: HttpRequest) -> Result<Exchange, ExecutorError> {
+        let body = self
+            .body
+            .bytes()
+            .map(|b| b.to_vec())
+            .map_err(|err| ExecutorError::FailedToReadBody(err.to_string()))?;
+        let timing = Timing {
+            headers_ms: self.headers_ms,
+            total_ms: millis(self.started.elapsed()),
+        };
+        let response = HttpResponse {
+            status: self.status,
+            version: self.version,
+            headers: self.headers,
+            body,
+        };
+        Result::Ok(Exchange {
+            request,
+            response,
+            timing,
+        })
+    }
+}
+
+impl Read for ResponseStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.body.read(buf)
+    }
+}
+
+/// The request as it was sent, with the credentials masked.
+#[derive(Serialize)]
+pub struct HttpRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<Header>,
+    /// Left out for bodies streamed from a file.
+    #[serde(serialize_with = "serialize_optional_body")]
+    pub body: Option<Vec<u8>>,
+}
+
+/// Milliseconds from sending the request to receiving the response headers,
+/// and to reading the whole body.
+#[derive(Serialize)]
+pub struct Timing {
+    pub headers_ms: u64,
+    pub total_ms: u64,
+}
+
+/// A request along with its response, the shape printed by `--output`:
+///
+/// ```yaml
+/// request: {method, url, headers: [{key, value}], body}
+/// response: {status, version, headers: [{key, value}], body}
+/// timing: {headers_ms, total_ms}
+/// ```
+///
+/// A body is `{encoding, content}` where the encoding is `utf-8`, or `base64`
+/// for content that is not valid UTF-8.
+#[derive(Serialize)]
+pub struct Exchange {
+    pub request: HttpRequest,
+    pub response: HttpResponse,
+    pub timing: Timing,
+}
+
+#[derive(Serialize)]
+struct EncodedBody {
+    encoding: &'static str,
+    content: String,
+}
+
+impl From<&[u8]> for EncodedBody {
+    fn from(body: &[u8]) -> Self {
+        match std::str::from_utf8(body) {
+            Ok(text) => EncodedBody {
+                encoding: "utf-8",
+                content: text.to_string(),
+            },
+            Err(_) => EncodedBody {
+                encoding: "base64",
+                content: STANDARD.encode(body),
+            },
+        }
+    }
+}
+
+pub fn serialize_body<S: Serializer>(body: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    EncodedBody::from(body).serialize(serializer)
+}
+
+fn serialize_optional_body<S: Serializer>(
+    body: &Option<Vec<u8>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    body.as_deref().map(EncodedBody::from).serialize(serializer)
+}
+
+/// Whether a header or query parameter name looks like it holds a secret,
+/// e.g. `X-Api-Key`, `access_token` or `client_secret`.
+fn is_secret_name(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    [
+        "token",
+        "secret",
+        "password",
+        "passwd",
+        "signature",
+        "api-key",
+        "api_key",
+        "apikey",
+    ]
+    .iter()
+    .any(|part| name.contains(part))
+        || matches!(name.as_str(), "key" | "sig" | "auth" | "session")
+}
+
+/// Masks the credentials of a header, keeping the auth scheme and the cookie
+/// names readable.
+fn mask_header(key: &str, value: &str) -> String {
+    let mask_cookie = |cookie: &str| match cookie.trim().split_once('=') {
+        Some((name, _)) => format!("{}={}", name, MASK),
+        None => MASK.to_string(),
+    };
+    match key.to_ascii_lowercase().as_str() {
+        "authorization" | "proxy-authorization" => match value.split_once(' ') {
+            Some((scheme, _)) => format!("{} {}", scheme, MASK),
+            None => MASK.to_string(),
+        },
+        "cookie" => value
+            .split(';')
+            .map(mask_cookie)
+            .collect::<Vec<String>>()
+            .join("; "),
+        "set-cookie" => match value.split_once(';') {
+            Some((cookie, attributes)) => format!("{};{}", mask_cookie(cookie), attributes),
+            None => mask_cookie(value),
+        },
+        key if is_secret_name(key) => MASK.to_string(),
+        _ => value.to_string(),
+    }
+}
+
+/// Masks the user info of a URL and the query parameters holding secrets.
+fn mask_url(url: &reqwest::Url) -> String {
+    let mut url = url.clone();
+    if !url.username().is_empty() {
+        let _ = url.set_username(MASK);
+    }
+    if url.password().is_some() {
+        let _ = url.set_password(Some(MASK));
+    }
+    if url.query_pairs().any(|(name, _)| is_secret_name(&name)) {
+        let pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .map(|(name, value)| match is_secret_name(&name) {
+                true => (name.into_owned(), MASK.to_string()),
+                false => (name.into_owned(), value.into_owned()),
+            })
+            .collect();
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    url.to_string()
+}
+
+fn serialize_masked_headers<S: Serializer>(
+    headers: &[Header],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let masked: Vec<Header> = headers
+        .iter()
+        .map(|h| Header {
+            key: h.key.clone(),
+            value: mask_header(&h.key, &h.value),
+        })
+        .collect();
+    masked.serialize(serializer)
+}
+
+impl From<&Request> for HttpRequest {
+    fn from(request: &Request) -> Self {
+        let headers = request
+            .headers()
+            .iter()
+            .map(|(key, value)| Header {
+                key: key.to_string(),
+                value: mask_header(key.as_str(), &String::from_utf8_lossy(value.as_bytes())),
+            })
+            .collect();
+        HttpRequest {
+            method: request.method().to_string(),
+            url: mask_url(request.url()),
+            headers,
+            body: request
+                .body()
+                .and_then(|b| b.as_bytes())
+                .map(|b| b.to_vec()),
+        }
+    }
+}
+
+fn json_body<T: Serialize>(value: &T) -> Result<Vec<u8>, ExecutorError> {
+    serde_json::to_vec(value).map_err(|e| ExecutorError::HTTPRequestError(e.to_string()))
+}
+
+fn millis(duration: Duration) -> u64 {
+    duration.as_millis().try_into().unwrap_or(u64::MAX)
+}
+
+/// The API and endpoint being executed, used to point errors at the
+/// collection entry that caused them.
+struct Target<'a> {
+    api: &'a str,
+    endpoint: &'a str,
+}
+
 pub struct Engine {
     resolver: Box<dyn Resolver>,
     http_client: reqwest::blocking::Client,
+    /// Sent on top of the endpoint headers, e.g. `Last-Event-ID` when resuming
+    /// an event stream.
+    extra_headers: HeaderMap,
+    /// Replaces the operation of a GraphQL endpoint, e.g. to introspect it.
+    graphql_query: Option<String>,
+    /// Variables taking precedence over the context, e.g. the element of a
+    /// flow loop.
+    overrides: Session,
 }
 
 impl Engine {
@@ -37,79 +329,257 @@ impl Engine {
         Engine {
             resolver,
             http_client: reqwest::blocking::Client::new(),
+            extra_headers: HeaderMap::new(),
+            graphql_query: None,
+            overrides: Session::new(),
+        }
+    }
+
+    pub fn set_graphql_query(&mut self, query: &str) {
+        self.graphql_query = Some(query.to_string());
+    }
+
+    /// Makes captured values available to the templates, the context and the
+    /// inputs take precedence over them.
+    pub fn add_session(&mut self, session: &Session) {
+        for (k, v) in session.iter() {
+            self.resolver.add_context(k.clone(), v)
         }
     }
 
-    fn resolve_headers(&mut self, headers: &HashMap<String, String>) -> HeaderMap {
+    /// Sets variables that win over the context and the inputs.
+    pub fn set_overrides(&mut self, variables: &Session) {
+        self.overrides = variables.clone();
+    }
+
+    pub fn set_header(&mut self, key: &'static str, value: &str) -> Result<(), ExecutorError> {
+        let value = HeaderValue::from_str(value)
+            .map_err(|e| ExecutorError::FailedToParseHeader(key.to_string(), e.to_string()))?;
+        self.extra_headers.insert(key, value);
+        Ok(())
+    }
+
+    fn resolve(
+        &mut self,
+        target: &Target,
+        field: &str,
+        tpl: &str,
+    ) -> Result<String, ExecutorError> {
+        self.resolver.resolve(tpl).map_err(|err| {
+            ExecutorError::FailedToResolveTemplate(
+                target.api.to_string(),
+                target.endpoint.to_string(),
+                field.to_string(),
+                err,
+            )
+        })
+    }
+
+    fn resolve_headers(
+        &mut self,
+        target: &Target,
+        headers: &HashMap<String, String>,
+    ) -> Result<HeaderMap, ExecutorError> {
         let mut header_map = HeaderMap::new();
         for (k, v) in headers.iter() {
-            let header_name = HeaderName::from_str(k).unwrap();
-            let header_value = HeaderValue::from_str(self.resolver.resolve(v).as_str()).unwrap();
+            let header_name = HeaderName::from_str(k).map_err(|e| {
+                ExecutorError::InvalidHeaderName(
+                    target.api.to_string(),
+                    target.endpoint.to_string(),
+                    k.clone(),
+                    e.to_string(),
+                )
+            })?;
+            let value = self.resolve(target, &format!("header {}", k), v)?;
+            let header_value = HeaderValue::from_str(value.as_str()).map_err(|e| {
+                ExecutorError::InvalidHeaderValue(
+                    target.api.to_string(),
+                    target.endpoint.to_string(),
+                    k.clone(),
+                    e.to_string(),
+                )
+            })?;
             header_map.insert(header_name, header_value);
         }
-        header_map
+        Ok(header_map)
     }
 
+    /// Adds the body, a file is streamed unless `buffered` asks to read it
+    /// first for the endpoints that send the body from memory.
     fn add_body(
         &mut self,
+        target: &Target,
         request: RequestBuilder,
         maybe_body: Option<&APIBody>,
-    ) -> RequestBuilder {
-        if maybe_body.is_none() {
-            return request;
-        }
-        let body = maybe_body.unwrap();
-        let content = self.resolver.resolve(&body.content);
+        buffered: bool,
+    ) -> Result<RequestBuilder, ExecutorError> {
+        let body = match maybe_body {
+            Some(body) => body,
+            None => return Ok(request),
+        };
+        let content = self.resolve(target, "body", &body.content)?;
         let body_req = match body.api_body_type {
-            config::APIBodyType::FILE => Body::new(File::open(PathBuf::from(content)).unwrap()),
+            config::APIBodyType::FILE if buffered => Body::from(
+                std::fs::read(&content)
+                    .map_err(|e| ExecutorError::FailedToOpenBodyFile(content, e.to_string()))?,
+            ),
+            config::APIBodyType::FILE => Body::new(
+                File::open(PathBuf::from(&content))
+                    .map_err(|e| ExecutorError::FailedToOpenBodyFile(content, e.to_string()))?,
+            ),
             config::APIBodyType::STRING => Body::from(content),
         };
-        request.body(body_req)
+        Ok(request.body(body_req))
     }
 
-    fn map_response(response: Response) -> Result<HttpResponse, ExecutorError> {
-        let status = response.status().as_u16();
-        let mut headers: Vec<Header> = Vec::with_capacity(response.headers().capacity());
-        for (hk, hv) in response.headers().iter() {
-            let key = hk.to_string();
-            let value = String::from_utf8(hv.as_bytes().into())
-                .map_err(|e| ExecutorError::FailedToParseHeader(key.clone(), e.to_string()))?;
-            headers.push(Header {
-                key,
-                value,
-            })
+    /// Templates every string nested in a variable value, `path` locating
+    /// the value in the errors, e.g. `filter.tags[0]`.
+    fn resolve_value(
+        &mut self,
+        target: &Target,
+        path: &str,
+        value: &Value,
+    ) -> Result<Value, ExecutorError> {
+        match value {
+            Value::String(template) => Ok(Value::String(self.resolve(
+                target,
+                &format!("graphql variable {}", path),
+                template,
+            )?)),
+            Value::Object(map) => {
+                let mut resolved = serde_json::Map::with_capacity(map.len());
+                for (key, value) in map.iter() {
+                    let path = match path {
+                        "" => key.clone(),
+                        _ => format!("{}.{}", path, key),
+                    };
+                    resolved.insert(key.clone(), self.resolve_value(target, &path, value)?);
+                }
+                Ok(Value::Object(resolved))
+            }
+            Value::Array(values) => values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| {
+                    self.resolve_value(target, &format!("{}[{}]", path, index), value)
+                })
+                .collect::<Result<Vec<Value>, ExecutorError>>()
+                .map(Value::Array),
+            value => Ok(value.clone()),
         }
-        let version = format!("{:?}", response.version());
-        let body = response
-            .bytes()
-            .map(|b| b.to_vec())
-            .map_err(|err| ExecutorError::FailedToReadBody(err.to_string()))?;
-        Result::Ok(HttpResponse {
-            status,
-            version,
-            headers,
-            body,
-        })
     }
 
-    fn add_auth(&mut self, request: RequestBuilder, auth_endpoint: &AuthEndpoint) -> RequestBuilder {
+    /// Templates the strings nested in the variables, or renders a template of
+    /// the whole object.
+    fn resolve_variables(
+        &mut self,
+        target: &Target,
+        variables: &Value,
+    ) -> Result<Value, ExecutorError> {
+        match variables {
+            Value::String(template) => {
+                let rendered = self.resolve(target, "graphql variables", template)?;
+                serde_json::from_str(&rendered).map_err(|e| {
+                    ExecutorError::InvalidGraphQLVariables(
+                        target.api.to_string(),
+                        target.endpoint.to_string(),
+                        e.to_string(),
+                    )
+                })
+            }
+            value => self.resolve_value(target, "", value),
+        }
+    }
+
+    /// Sends the operation as the standard JSON body.
+    fn add_graphql(
+        &mut self,
+        target: &Target,
+        request: RequestBuilder,
+        graphql: &APIGraphQL,
+    ) -> Result<RequestBuilder, ExecutorError> {
+        let operation = match &self.graphql_query {
+            Some(query) => json_body(&Operation {
+                query,
+                operation_name: None,
+                variables: None,
+            }),
+            None => {
+                let query = match graphql.query.api_body_type {
+                    config::APIBodyType::STRING => graphql.query.content.clone(),
+                    config::APIBodyType::FILE => {
+                        let path = self.resolve(target, "graphql query", &graphql.query.content)?;
+                        std::fs::read_to_string(&path)
+                            .map_err(|e| ExecutorError::FailedToOpenBodyFile(path, e.to_string()))?
+                    }
+                };
+                let variables = match &graphql.variables {
+                    Some(variables) => Some(self.resolve_variables(target, variables)?),
+                    None => None,
+                };
+                json_body(&Operation {
+                    query: &query,
+                    operation_name: graphql.operation_name.as_deref(),
+                    variables,
+                })
+            }
+        }?;
+        Ok(request.body(operation))
+    }
+
+    fn add_query(
+        &mut self,
+        target: &Target,
+        request: RequestBuilder,
+        query: &HashMap<String, String>,
+    ) -> Result<RequestBuilder, ExecutorError> {
+        let mut resolved: Vec<(&String, String)> = Vec::with_capacity(query.len());
+        for (k, v) in query.iter() {
+            resolved.push((k, self.resolve(target, &format!("query {}", k), v)?));
+        }
+        resolved.sort();
+        Ok(request.query(&resolved))
+    }
+
+    fn build_client(client: &APIClient) -> Result<Client, ExecutorError> {
+        let mut builder = Client::builder();
+        if let Some(timeout) = client.timeout {
+            builder = builder.timeout(Duration::from_secs(timeout));
+        }
+        if let Some(insecure) = client.insecure {
+            builder = builder.danger_accept_invalid_certs(insecure);
+        }
+        if client.follow_redirects == Some(false) {
+            builder = builder.redirect(Policy::none());
+        }
+        builder
+            .build()
+            .map_err(|e| ExecutorError::HTTPRequestError(e.to_string()))
+    }
+
+    fn add_auth(
+        &mut self,
+        target: &Target,
+        request: RequestBuilder,
+        auth_endpoint: &AuthEndpoint,
+    ) -> Result<RequestBuilder, ExecutorError> {
         match auth_endpoint {
-            AuthEndpoint::Basic { username, password } => 
-                request.basic_auth(
-                    self.resolver.resolve(username), 
-                    Some(self.resolver.resolve(password))
-                ),
-            _ => request
+            AuthEndpoint::Basic { username, password } => Ok(request.basic_auth(
+                self.resolve(target, "auth username", username)?,
+                Some(self.resolve(target, "auth password", password)?),
+            )),
         }
     }
 
-    pub fn run(
+    /// Resolves the endpoint into a request along with the client to send it with.
+    fn build(
         &mut self,
+        api: &str,
         api_config: &APIConfig,
         endpoint: &str,
         maybe_context: &Option<String>,
-        inputs: &Vec<(String, String)>,
-    ) -> Result<HttpResponse, ExecutorError> {
+        inputs: &[(String, String)],
+    ) -> Result<(Client, Request), ExecutorError> {
         if let Some(context) = maybe_context {
             self.resolver.add_context(CONTEXT_KEY.to_string(), context)
         }
@@ -124,31 +594,136 @@ impl Engine {
                 self.resolver.add_context(k.clone(), v.as_str())
             }
         }
-        let api_endpoint = api_config.get_api_endpoint(endpoint).unwrap();
-        let url = self.resolver.resolve(&api_endpoint.url);
-        let resolved_headers = api_endpoint
-            .headers
-            .as_ref()
-            .map(|h| self.resolve_headers(h))
-            .unwrap_or_else(HeaderMap::new);
+        for (k, v) in self.overrides.iter() {
+            self.resolver.add_context(k.clone(), v)
+        }
+        let api_endpoint = api_config
+            .get_api_endpoint(endpoint)
+            .ok_or_else(|| ExecutorError::EndpointNotFound(endpoint.to_string()))?;
+        let target = Target { api, endpoint };
+        let url = self.resolve(&target, "url", &api_endpoint.url)?;
+        let mut resolved_headers = match &api_endpoint.headers {
+            Some(headers) => self.resolve_headers(&target, headers)?,
+            None => HeaderMap::new(),
+        };
+        if api_endpoint.stream == Some(APIStream::Sse) && !resolved_headers.contains_key(ACCEPT) {
+            resolved_headers.insert(ACCEPT, HeaderValue::from_static(EVENT_STREAM));
+        }
+        resolved_headers.extend(self.extra_headers.clone());
+        let resolved_content_type = resolved_headers.contains_key(CONTENT_TYPE);
+        let http_client = match &api_endpoint.client {
+            Some(client) => Self::build_client(client)?,
+            None => self.http_client.clone(),
+        };
         let request = match &api_endpoint.method {
-            config::APIMethod::GET => self.http_client.get(&url),
-            config::APIMethod::POST => self.http_client.post(&url),
-            config::APIMethod::DELETE => self.http_client.delete(&url),
-            config::APIMethod::PATCH => self.http_client.patch(&url),
+            config::APIMethod::GET | config::APIMethod::WS => http_client.get(&url),
+            config::APIMethod::POST => http_client.post(&url),
+            config::APIMethod::DELETE => http_client.delete(&url),
+            config::APIMethod::PATCH => http_client.patch(&url),
+        };
+        let request = match &api_endpoint.query {
+            Some(query) => self.add_query(&target, request, query)?,
+            None => request,
         };
-        let request = if let Some(auth_endpoint) = &api_endpoint.auth  {
-            self.add_auth(request, auth_endpoint)
+        let request = if let Some(auth_endpoint) = &api_endpoint.auth {
+            self.add_auth(&target, request, auth_endpoint)?
         } else {
             request
         }
         .headers(resolved_headers);
 
-        let request = self.add_body(request, api_endpoint.body.as_ref());
-        request
-            .send()
-            .map_err(|e| ExecutorError::HTTPRequestError(e.to_string()))
-            .and_then(Self::map_response)
+        let request = match (&api_endpoint.endpoint_type, &api_endpoint.graphql) {
+            (APIEndpointType::GraphQL, Some(graphql)) => {
+                let request = match resolved_content_type {
+                    true => request,
+                    false => request.header(CONTENT_TYPE, "application/json"),
+                };
+                self.add_graphql(&target, request, graphql)?
+            }
+            (endpoint_type, _) => {
+                let buffered = *endpoint_type == APIEndpointType::Grpc
+                    || matches!(api_endpoint.method, config::APIMethod::WS);
+                self.add_body(&target, request, api_endpoint.body.as_ref(), buffered)?
+            }
+        }
+        .build()
+        .map_err(|e| ExecutorError::HTTPRequestError(e.to_string()))?;
+        Ok((http_client, request))
+    }
+
+    /// Sends the request and returns as soon as the response headers are in,
+    /// the body is read from the returned stream.
+    pub fn stream(
+        &mut self,
+        api: &str,
+        api_config: &APIConfig,
+        endpoint: &str,
+        maybe_context: &Option<String>,
+        inputs: &[(String, String)],
+    ) -> Result<(HttpRequest, ResponseStream), ExecutorError> {
+        let (http_client, request) =
+            self.build(api, api_config, endpoint, maybe_context, inputs)?;
+        let sent = HttpRequest::from(&request);
+        let started = Instant::now();
+        let response = http_client
+            .execute(request)
+            .map_err(|e| ExecutorError::HTTPRequestError(e.to_string()))?;
+        Ok((sent, ResponseStream::new(response, started)?))
+    }
+
+    /// Calls the method of a `type: grpc` endpoint, the reply is read as a
+    /// JSON response.
+    pub fn call_grpc(
+        &mut self,
+        api: &str,
+        api_config: &APIConfig,
+        endpoint: &str,
+        maybe_context: &Option<String>,
+        inputs: &[(String, String)],
+    ) -> Result<Exchange, ExecutorError> {
+        let grpc = api_config
+            .get_api_endpoint(endpoint)
+            .and_then(|e| e.grpc.as_ref())
+            .ok_or_else(|| ExecutorError::EndpointNotFound(endpoint.to_string()))?;
+        let (_, request) = self.build(api, api_config, endpoint, maybe_context, inputs)?;
+        let started = Instant::now();
+        let response = grpc::call(&request, grpc)?;
+        let elapsed = millis(started.elapsed());
+        Ok(Exchange {
+            request: HttpRequest::from(&request),
+            response,
+            timing: Timing {
+                headers_ms: elapsed,
+                total_ms: elapsed,
+            },
+        })
+    }
+
+    /// Opens a WebSocket to the endpoint URL with the endpoint headers and
+    /// auth, the resolved body is returned as the first message to send.
+    pub fn connect(
+        &mut self,
+        api: &str,
+        api_config: &APIConfig,
+        endpoint: &str,
+        maybe_context: &Option<String>,
+        inputs: &[(String, String)],
+    ) -> Result<(WebSocket, Option<Vec<u8>>), ExecutorError> {
+        let (_, request) = self.build(api, api_config, endpoint, maybe_context, inputs)?;
+        let mut handshake = request
+            .url()
+            .as_str()
+            .into_client_request()
+            .map_err(|e| ExecutorError::HTTPRequestError(e.to_string()))?;
+        handshake.headers_mut().extend(request.headers().clone());
+        let first_message = request
+            .body()
+            .and_then(|b| b.as_bytes())
+            .map(|b| b.to_vec());
+        let client = api_config
+            .get_api_endpoint(endpoint)
+            .and_then(|e| e.client.as_ref());
+        WebSocket::connect(handshake, client).map(|socket| (socket, first_message))
     }
 }
 
@@ -0,0 +1,281 @@
+use serde_json::Value;
+
+use super::error::ExecutorError;
+
+/// One step of a path, e.g. `.items`, `[0]`, `[]` or `[1:3]`.
+#[derive(Debug, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(i64),
+    Slice(Option<i64>, Option<i64>),
+    Iterate,
+}
+
+#[derive(Debug, PartialEq)]
+struct Step {
+    segment: Segment,
+    /// Set with a trailing `?`, errors of the step yield nothing instead.
+    optional: bool,
+}
+
+/// A jq style filter such as `.items[].id` or `.data | .name, .id`, JSONPath
+/// expressions such as `$.items[*].id` are read the same way. Only paths,
+/// pipes and commas are supported.
+#[derive(Debug)]
+pub struct Filter {
+    expression: String,
+    /// The stages of the pipeline, each one made of the paths separated by
+    /// `,` that are all applied to every value coming from the stage before.
+    stages: Vec<Vec<Vec<Step>>>,
+}
+
+fn parse_error(expression: &str, message: &str) -> ExecutorError {
+    ExecutorError::InvalidFilter(expression.to_string(), message.to_string())
+}
+
+/// Splits on a separator outside of quotes and brackets.
+fn split_top_level(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    let mut start = 0;
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(_), _) if escaped => escaped = false,
+            (Some(_), '\\') => escaped = true,
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => (),
+            (None, c) if c == separator && depth == 0 => {
+                parts.push(&text[start..i]);
+                start = i + c.len_utf8();
+            }
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '[') => depth += 1,
+            (None, ']') => depth -= 1,
+            _ => (),
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+/// Byte offset of the quote closing the string `text` starts with.
+fn closing_quote(text: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in text.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some(i),
+            _ => (),
+        }
+    }
+    None
+}
+
+fn parse_quoted(text: &str) -> Option<String> {
+    let quote = text.chars().next()?;
+    if text.len() < 2 || !text.ends_with(quote) {
+        return None;
+    }
+    match quote {
+        '"' => serde_json::from_str(text).ok(),
+        '\'' => Some(text[1..text.len() - 1].replace("\\'", "'")),
+        _ => None,
+    }
+}
+
+fn parse_bracket(content: &str) -> Option<Segment> {
+    let content = content.trim();
+    if content.is_empty() || content == "*" {
+        return Some(Segment::Iterate);
+    }
+    if let Some(key) = parse_quoted(content) {
+        return Some(Segment::Key(key));
+    }
+    if let Some((start, end)) = content.split_once(':') {
+        let bound = |b: &str| match b.trim() {
+            "" => Some(None),
+            b => b.parse().ok().map(Some),
+        };
+        return Some(Segment::Slice(bound(start)?, bound(end)?));
+    }
+    content.parse().ok().map(Segment::Index)
+}
+
+fn parse_path(expression: &str, path: &str) -> Result<Vec<Step>, ExecutorError> {
+    let path = path.trim();
+    let mut rest = match path.strip_prefix('$') {
+        Some(rest) => rest,
+        None if path.starts_with('.') => path,
+        None => {
+            return Err(parse_error(
+                expression,
+                &format!("`{}` must start with `.` or `$`", path),
+            ))
+        }
+    };
+    if rest == "." {
+        return Ok(Vec::new());
+    }
+    let mut steps = Vec::new();
+    while !rest.is_empty() {
+        let segment = if let Some(after) = rest.strip_prefix('[') {
+            let end = split_top_level(after, ']')[0].len();
+            if end >= after.len() {
+                return Err(parse_error(expression, "unclosed `[`"));
+            }
+            let segment = parse_bracket(&after[..end]).ok_or_else(|| {
+                parse_error(expression, &format!("invalid index `[{}]`", &after[..end]))
+            })?;
+            rest = &after[end + 1..];
+            segment
+        } else if let Some(after) = rest.strip_prefix('.') {
+            if after.starts_with('[') {
+                rest = after;
+                continue;
+            }
+            if let Some(after) = after.strip_prefix('*') {
+                rest = after;
+                Segment::Iterate
+            } else if after.starts_with('"') {
+                let end = closing_quote(after)
+                    .ok_or_else(|| parse_error(expression, "unclosed `\"`"))?
+                    + 1;
+                let key = parse_quoted(&after[..end])
+                    .ok_or_else(|| parse_error(expression, "invalid quoted key"))?;
+                rest = &after[end..];
+                Segment::Key(key)
+            } else {
+                let end = after
+                    .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+                    .unwrap_or(after.len());
+                if end == 0 {
+                    return Err(parse_error(
+                        expression,
+                        &format!("expected a key after `.` in `{}`", path),
+                    ));
+                }
+                rest = &after[end..];
+                Segment::Key(after[..end].to_string())
+            }
+        } else {
+            return Err(parse_error(expression, &format!("unexpected `{}`", rest)));
+        };
+        let optional = rest.starts_with('?');
+        if optional {
+            rest = &rest[1..];
+        }
+        steps.push(Step { segment, optional });
+    }
+    Ok(steps)
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Resolves a negative index from the end and clamps it to the length.
+fn position(index: i64, len: usize) -> usize {
+    match index < 0 {
+        true => len.saturating_sub(index.unsigned_abs() as usize),
+        false => (index as usize).min(len),
+    }
+}
+
+fn apply(segment: &Segment, value: &Value) -> Result<Vec<Value>, String> {
+    match (segment, value) {
+        (Segment::Key(_) | Segment::Index(_) | Segment::Slice(_, _), Value::Null) => {
+            Ok(vec![Value::Null])
+        }
+        (Segment::Key(key), Value::Object(map)) => {
+            Ok(vec![map.get(key).cloned().unwrap_or(Value::Null)])
+        }
+        (Segment::Index(index), Value::Array(items)) => {
+            let item = match *index < 0 && index.unsigned_abs() as usize > items.len() {
+                true => None,
+                false => items.get(position(*index, items.len())),
+            };
+            Ok(vec![item.cloned().unwrap_or(Value::Null)])
+        }
+        (Segment::Slice(start, end), Value::Array(items)) => {
+            let start = start.map(|s| position(s, items.len())).unwrap_or(0);
+            let end = end.map(|e| position(e, items.len())).unwrap_or(items.len());
+            Ok(vec![Value::Array(items[start..end.max(start)].to_vec())])
+        }
+        (Segment::Iterate, Value::Array(items)) => Ok(items.clone()),
+        (Segment::Iterate, Value::Object(map)) => Ok(map.values().cloned().collect()),
+        (Segment::Key(key), value) => Err(format!(
+            "cannot index {} with \"{}\"",
+            type_name(value),
+            key
+        )),
+        (Segment::Iterate, value) => Err(format!("cannot iterate over {}", type_name(value))),
+        (_, value) => Err(format!("cannot index {} with a number", type_name(value))),
+    }
+}
+
+impl Filter {
+    pub fn parse(expression: &str) -> Result<Filter, ExecutorError> {
+        if expression.trim().is_empty() {
+            return Err(parse_error(expression, "empty filter"));
+        }
+        let stages = split_top_level(expression, '|')
+            .into_iter()
+            .map(|stage| {
+                split_top_level(stage, ',')
+                    .into_iter()
+                    .map(|path| parse_path(expression, path))
+                    .collect::<Result<Vec<Vec<Step>>, ExecutorError>>()
+            })
+            .collect::<Result<_, ExecutorError>>()?;
+        Ok(Filter {
+            expression: expression.to_string(),
+            stages,
+        })
+    }
+
+    fn apply_path(&self, steps: &[Step], value: &Value) -> Result<Vec<Value>, ExecutorError> {
+        let mut values = vec![value.clone()];
+        for step in steps.iter() {
+            let mut next = Vec::new();
+            for value in values.iter() {
+                match apply(&step.segment, value) {
+                    Ok(applied) => next.extend(applied),
+                    Err(_) if step.optional => (),
+                    Err(e) => return Err(ExecutorError::FilterFailed(self.expression.clone(), e)),
+                }
+            }
+            values = next;
+        }
+        Ok(values)
+    }
+
+    /// Every value the filter yields, in order. As in jq `|` binds looser
+    /// than `,`, so `.data | .name, .id` reads both keys of `.data`.
+    pub fn apply(&self, value: &Value) -> Result<Vec<Value>, ExecutorError> {
+        let mut values = vec![value.clone()];
+        for paths in self.stages.iter() {
+            let mut next = Vec::new();
+            for value in values.iter() {
+                for steps in paths.iter() {
+                    next.extend(self.apply_path(steps, value)?);
+                }
+            }
+            values = next;
+        }
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+#[path = "./filter_test.rs"]
+mod filter_test;
@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::cmd::error::ExecutorError;
+    use crate::cmd::filter::Filter;
+
+    fn apply(expression: &str) -> Vec<serde_json::Value> {
+        let body = json!({
+            "items": [{"id": 1, "tags": ["a"]}, {"id": 2, "tags": []}, {"id": 3}],
+            "a key": {"b": true},
+            "count": 3
+        });
+        Filter::parse(expression)
+            .and_then(|filter| filter.apply(&body))
+            .unwrap_or_else(|e| panic!("{}: {}", expression, e))
+    }
+
+    #[test]
+    fn filter_paths_test() {
+        assert_eq!(apply(".count"), vec![json!(3)]);
+        assert_eq!(apply(".items[].id"), vec![json!(1), json!(2), json!(3)]);
+        assert_eq!(apply("$.items[*].id"), vec![json!(1), json!(2), json!(3)]);
+        assert_eq!(apply(".items[-1]"), vec![json!({"id": 3})]);
+        assert_eq!(apply(".items[5].id"), vec![json!(null)]);
+        assert_eq!(apply(".items[1:] | .[].id"), vec![json!(2), json!(3)]);
+        assert_eq!(
+            apply(r#"."a key".b, $['a key']["b"]"#),
+            vec![json!(true), json!(true)]
+        );
+        assert_eq!(apply(".items[].tags[]?"), vec![json!("a")]);
+        assert_eq!(apply(".count[]?, .count"), vec![json!(3)]);
+        assert_eq!(apply(".").len(), 1);
+        // The pipe binds looser than the comma, as in jq.
+        assert_eq!(
+            apply(".items[0] | .id, .tags"),
+            vec![json!(1), json!(["a"])]
+        );
+        assert_eq!(
+            apply(".items[:2][] | .id, .tags[0]?"),
+            vec![json!(1), json!("a"), json!(2), json!(null)]
+        );
+    }
+
+    #[test]
+    fn filter_errors_test() {
+        for expression in ["", "items", ".items[", ".items[x]", ".a..b"] {
+            assert!(
+                matches!(
+                    Filter::parse(expression),
+                    Err(ExecutorError::InvalidFilter(_, _))
+                ),
+                "{}",
+                expression
+            );
+        }
+        let filter = Filter::parse(".count[]").unwrap();
+        assert!(matches!(
+            filter.apply(&json!({"count": 3})),
+            Err(ExecutorError::FilterFailed(_, _))
+        ));
+    }
+}
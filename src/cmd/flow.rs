@@ -0,0 +1,272 @@
+use std::path::Path;
+
+use serde_json::Value;
+
+use super::{
+    capture::{self, Session},
+    config::{APIConfig, APIEndpointType, APIFlowStep, APIMethod},
+    error::ExecutorError,
+    executor::{Engine, Exchange, CONTEXT_KEY},
+    graphql, input,
+    resolver::{self, Resolver},
+    run::check_status,
+};
+
+/// The variable holding the element of a `for_each` step without `as`.
+pub const DEFAULT_ITEM: &str = "item";
+
+/// What a flow is run with, shared by its steps.
+struct FlowRun<'a> {
+    api: &'a str,
+    api_config: &'a APIConfig,
+    flow: &'a str,
+    context: &'a Option<String>,
+    inputs: &'a [(String, String)],
+}
+
+impl FlowRun<'_> {
+    /// A resolver seeing the session and the captures, then the context and
+    /// then the `-i` inputs and the loop item, with the precedence the
+    /// `Engine` gives them.
+    fn resolver(&self, variables: &Session, explicit: &Session) -> Box<dyn Resolver> {
+        let mut resolver = resolver::new();
+        for (k, v) in variables.iter() {
+            resolver.add_context(k.clone(), v);
+        }
+        if let Some(context) = self.context {
+            resolver.add_context(CONTEXT_KEY.to_string(), context);
+        }
+        let values = self
+            .context
+            .as_ref()
+            .and_then(|c| self.api_config.get_api_context(c));
+        for (k, v) in values.into_iter().flatten() {
+            resolver.add_context(k.clone(), v);
+        }
+        for (k, v) in explicit.iter() {
+            resolver.add_context(k.clone(), v);
+        }
+        resolver
+    }
+
+    fn resolve(
+        &self,
+        resolver: &mut dyn Resolver,
+        label: &str,
+        field: &str,
+        tpl: &str,
+    ) -> Result<String, ExecutorError> {
+        resolver.resolve(tpl).map_err(|e| {
+            ExecutorError::FailedToResolveTemplate(
+                self.api.to_string(),
+                self.flow.to_string(),
+                format!("{} of step {}", field, label),
+                e,
+            )
+        })
+    }
+
+    fn invalid(&self, label: &str, reason: String) -> ExecutorError {
+        ExecutorError::InvalidFlowStep(self.flow.to_string(), label.to_string(), reason)
+    }
+
+    /// The elements a `for_each` step iterates over, strings are taken as is.
+    fn elements(
+        &self,
+        label: &str,
+        variables: &Session,
+        variable: &str,
+    ) -> Result<Vec<String>, ExecutorError> {
+        let value = variables.get(variable).ok_or_else(|| {
+            self.invalid(label, format!("no variable `{}` to iterate over", variable))
+        })?;
+        match serde_json::from_str(value) {
+            Ok(Value::Array(elements)) => Ok(elements
+                .into_iter()
+                .map(|element| match element {
+                    Value::String(text) => text,
+                    element => element.to_string(),
+                })
+                .collect()),
+            _ => Err(self.invalid(label, format!("`{}` is not a JSON array", variable))),
+        }
+    }
+
+    /// Sends the request of a step, checks the response as `--fail` would and
+    /// reads what the endpoint and the step capture. The session and the
+    /// captures have the lowest precedence, the `-i` inputs and the loop item
+    /// win over the context and the inputs of the step win over everything.
+    fn call(
+        &self,
+        label: &str,
+        step: &APIFlowStep,
+        variables: &Session,
+        explicit: &Session,
+        inputs: &[(String, String)],
+    ) -> Result<(Exchange, Session), ExecutorError> {
+        let endpoint = &step.endpoint;
+        let api_endpoint = self
+            .api_config
+            .get_api_endpoint(endpoint)
+            .ok_or_else(|| ExecutorError::EndpointNotFound(endpoint.clone()))?;
+        let mut requested: Vec<(String, String)> = self
+            .inputs
+            .iter()
+            .filter(|(key, _)| !inputs.iter().any(|(k, _)| k == key))
+            .cloned()
+            .collect();
+        let requested = match &api_endpoint.inputs {
+            Some(declared) => {
+                // The `-i` inputs only go through the declaration of the
+                // endpoints declaring them, the others are for templates.
+                requested.retain(|(key, _)| declared.contains_key(key));
+                requested.extend(inputs.iter().cloned());
+                input::validate_inputs(endpoint, declared, &requested)?
+            }
+            None => {
+                requested.extend(inputs.iter().cloned());
+                requested
+            }
+        };
+        let mut overrides = explicit.clone();
+        overrides.extend(inputs.iter().cloned());
+        let mut engine = Engine::new();
+        engine.add_session(variables);
+        engine.set_overrides(&overrides);
+        let exchange = match (&api_endpoint.endpoint_type, &api_endpoint.method) {
+            (APIEndpointType::Grpc, _) => engine.call_grpc(
+                self.api,
+                self.api_config,
+                endpoint,
+                self.context,
+                &requested,
+            )?,
+            (_, APIMethod::WS) => {
+                return Err(self.invalid(label, "WebSocket endpoints cannot be steps".to_string()))
+            }
+            _ => {
+                let (request, stream) = engine.stream(
+                    self.api,
+                    self.api_config,
+                    endpoint,
+                    self.context,
+                    &requested,
+                )?;
+                stream.into_exchange(request)?
+            }
+        };
+        check_status(exchange.response.status, &api_endpoint.expect_status, true)?;
+        if api_endpoint.endpoint_type == APIEndpointType::GraphQL {
+            graphql::check_errors(&exchange.response.body, true)?;
+        }
+        let mut rules = api_endpoint.capture.clone().unwrap_or_default();
+        rules.extend(step.capture.clone().unwrap_or_default());
+        let captured = capture::capture(&rules, &exchange.response)?;
+        Ok((exchange, captured))
+    }
+
+    /// Runs a step once, or once per element with `for_each`, adding what it
+    /// captures to the variables.
+    fn step(
+        &self,
+        number: usize,
+        step: &APIFlowStep,
+        variables: &mut Session,
+        session_file: &Path,
+    ) -> Result<(), ExecutorError> {
+        let name = format!(
+            "{}. {}",
+            number,
+            step.name.as_ref().unwrap_or(&step.endpoint)
+        );
+        let items: Vec<Option<String>> = match &step.for_each {
+            Some(variable) => self
+                .elements(&name, variables, variable)?
+                .into_iter()
+                .map(Some)
+                .collect(),
+            None => vec![None],
+        };
+        let item_variable = step.item.as_deref().unwrap_or(DEFAULT_ITEM);
+        for (index, item) in items.into_iter().enumerate() {
+            let label = match step.for_each {
+                Some(_) => format!("{}[{}]", name, index),
+                None => name.clone(),
+            };
+            let mut explicit: Session = self.inputs.iter().cloned().collect();
+            if let Some(item) = item {
+                explicit.insert(item_variable.to_string(), item);
+            }
+            let mut resolver = self.resolver(variables, &explicit);
+            if let Some(condition) = &step.condition {
+                let rendered = self.resolve(resolver.as_mut(), &label, "if", condition)?;
+                if matches!(rendered.trim(), "" | "false" | "0") {
+                    println!("skipped {}", label);
+                    continue;
+                }
+            }
+            let mut inputs: Vec<(String, String)> = Vec::new();
+            for (key, tpl) in step.inputs.iter().flatten() {
+                let value =
+                    self.resolve(resolver.as_mut(), &label, &format!("input {}", key), tpl)?;
+                inputs.push((key.clone(), value));
+            }
+            let (exchange, captured) = match self.call(&label, step, variables, &explicit, &inputs)
+            {
+                Ok(called) => called,
+                Err(e) => {
+                    println!("failed  {}", label);
+                    return Err(e);
+                }
+            };
+            let mut line = format!(
+                "ok      {} {} {} -> {} ({} ms)",
+                label,
+                exchange.request.method,
+                exchange.request.url,
+                exchange.response.status,
+                exchange.timing.total_ms
+            );
+            if !captured.is_empty() {
+                let names: Vec<&str> = captured.keys().map(String::as_str).collect();
+                line.push_str(&format!(", captured {}", names.join(", ")));
+                capture::save(session_file, captured.clone())?;
+                variables.extend(captured);
+            }
+            println!("{}", line);
+        }
+        Ok(())
+    }
+}
+
+/// Runs the steps of a flow in order with the values captured earlier in the
+/// same API and context, stopping at the first step that fails.
+pub fn run(
+    api: &str,
+    api_config: &APIConfig,
+    flow: &str,
+    context: &Option<String>,
+    inputs: &[(String, String)],
+    session_directory: &Path,
+) -> Result<(), ExecutorError> {
+    let api_flow = api_config
+        .get_flow(flow)
+        .ok_or_else(|| ExecutorError::FlowNotFound(flow.to_string()))?;
+    let session_file = capture::session_file(session_directory, api, context);
+    let mut variables = capture::load(&session_file)?;
+    let flow_run = FlowRun {
+        api,
+        api_config,
+        flow,
+        context,
+        inputs,
+    };
+    for (index, step) in api_flow.steps.iter().enumerate() {
+        flow_run.step(index + 1, step, &mut variables, &session_file)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "./flow_test.rs"]
+mod flow_test;
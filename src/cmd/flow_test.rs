@@ -0,0 +1,172 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use httpmock::Method::{DELETE, GET, POST};
+    use httpmock::MockServer;
+
+    use crate::cmd::capture::{load, save, session_file, Session};
+    use crate::cmd::config::{APIConfig, APIContext, APIEndpoint, APIFlow};
+    use crate::cmd::error::ExecutorError;
+    use crate::cmd::flow::run;
+
+    const ENDPOINTS: &str = r#"
+login:
+  method: POST
+  url: "{{url}}/login"
+  capture:
+    token: {json: "$.token"}
+remove:
+  method: DELETE
+  url: "{{url}}/tenants/{{tenant}}"
+  headers:
+    Authorization: "Bearer {{token}}"
+report:
+  method: GET
+  url: "{{url}}/report"
+"#;
+
+    const FLOWS: &str = r#"
+teardown:
+  steps:
+    - endpoint: login
+      capture:
+        tenants: {json: ".tenants"}
+    - name: remove tenants
+      endpoint: remove
+      if: "{{ tenants != '[]' }}"
+      for_each: tenants
+      as: tenant
+    - endpoint: report
+      if: "{{ report }}"
+"#;
+
+    #[test]
+    fn flow_run_test() {
+        let server = MockServer::start();
+        let login = server.mock(|when, then| {
+            when.method(POST).path("/login");
+            then.status(200)
+                .body(r#"{"token": "t1", "tenants": ["a", "b"]}"#);
+        });
+        let removes: Vec<_> = ["a", "b"]
+            .iter()
+            .map(|tenant| {
+                server.mock(|when, then| {
+                    when.method(DELETE)
+                        .path(format!("/tenants/{}", tenant))
+                        .header("Authorization", "Bearer t1");
+                    then.status(204);
+                })
+            })
+            .collect();
+        let report = server.mock(|when, then| {
+            when.method(GET).path("/report");
+            then.status(200);
+        });
+        let endpoints: HashMap<String, APIEndpoint> = serde_yaml::from_str(ENDPOINTS).unwrap();
+        let flows: HashMap<String, APIFlow> = serde_yaml::from_str(FLOWS).unwrap();
+        // The loop variable wins over a context value of the same name.
+        let context: APIContext = HashMap::from([
+            ("url".to_string(), server.base_url()),
+            ("tenant".to_string(), "context".to_string()),
+        ]);
+        let api_config = APIConfig::new(
+            Some(HashMap::from([("local".to_string(), context)])),
+            endpoints,
+        )
+        .with_flows(flows);
+        let directory = std::env::temp_dir().join(format!("rbm-flow-{}", std::process::id()));
+        let context = Some("local".to_string());
+        let inputs = vec![("report".to_string(), "false".to_string())];
+
+        run(
+            "tenants",
+            &api_config,
+            "teardown",
+            &context,
+            &inputs,
+            &directory,
+        )
+        .unwrap();
+        login.assert();
+        for remove in removes.iter() {
+            remove.assert();
+        }
+        report.assert_hits(0);
+        let session = load(&session_file(&directory, "tenants", &context)).unwrap();
+        assert_eq!(session.get("token").map(String::as_str), Some("t1"));
+        assert_eq!(
+            session.get("tenants").map(String::as_str),
+            Some(r#"["a","b"]"#)
+        );
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    const ITEMS: &str = r#"
+list:
+  method: GET
+  url: "{{url}}/items"
+  capture:
+    id: {json: ".[0]"}
+    ids: {json: "."}
+get:
+  method: GET
+  url: "{{url}}/items/{{id}}"
+  inputs:
+    id: {type: int}
+"#;
+
+    const ITEMS_FLOWS: &str = r#"
+each:
+  steps:
+    - endpoint: list
+    - endpoint: get
+      for_each: ids
+      inputs:
+        id: "{{item}}"
+    - endpoint: get
+"#;
+
+    #[test]
+    fn flow_step_inputs_win_test() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/items");
+            then.status(200).body("[7, 8]");
+        });
+        let gets: Vec<_> = [("7", 2), ("8", 1)]
+            .iter()
+            .map(|(id, hits)| {
+                let mock = server.mock(|when, then| {
+                    when.method(GET).path(format!("/items/{}", id));
+                    then.status(200);
+                });
+                (mock, *hits)
+            })
+            .collect();
+        let endpoints: HashMap<String, APIEndpoint> = serde_yaml::from_str(ITEMS).unwrap();
+        let flows: HashMap<String, APIFlow> = serde_yaml::from_str(ITEMS_FLOWS).unwrap();
+        let context: APIContext = HashMap::from([("url".to_string(), server.base_url())]);
+        let api_config = APIConfig::new(
+            Some(HashMap::from([("local".to_string(), context)])),
+            endpoints,
+        )
+        .with_flows(flows);
+        let directory = std::env::temp_dir().join(format!("rbm-flow-items-{}", std::process::id()));
+        let context = Some("local".to_string());
+        // A stale session value loses to what the flow captures.
+        let file = session_file(&directory, "items", &context);
+        save(&file, Session::from([("id".to_string(), "1".to_string())])).unwrap();
+
+        run("items", &api_config, "each", &context, &[], &directory).unwrap();
+        for (mock, hits) in gets.iter() {
+            mock.assert_hits(*hits);
+        }
+        assert!(matches!(
+            run("items", &api_config, "each", &context, &[("id".to_string(), "x".to_string())], &directory),
+            Err(ExecutorError::InvalidInput(key, _)) if key == "id"
+        ));
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
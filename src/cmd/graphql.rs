@@ -0,0 +1,178 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::{
+    config::{APIConfig, APIEndpointType},
+    error::ExecutorError,
+    executor::Engine,
+    input,
+};
+
+/// The query GraphiQL and most tools send to fetch a schema.
+const INTROSPECTION_QUERY: &str = "query IntrospectionQuery {
+  __schema {
+    queryType { name }
+    mutationType { name }
+    subscriptionType { name }
+    types { ...FullType }
+    directives {
+      name
+      description
+      locations
+      args { ...InputValue }
+    }
+  }
+}
+
+fragment FullType on __Type {
+  kind
+  name
+  description
+  fields(includeDeprecated: true) {
+    name
+    description
+    args { ...InputValue }
+    type { ...TypeRef }
+    isDeprecated
+    deprecationReason
+  }
+  inputFields { ...InputValue }
+  interfaces { ...TypeRef }
+  enumValues(includeDeprecated: true) {
+    name
+    description
+    isDeprecated
+    deprecationReason
+  }
+  possibleTypes { ...TypeRef }
+}
+
+fragment InputValue on __InputValue {
+  name
+  description
+  type { ...TypeRef }
+  defaultValue
+}
+
+fragment TypeRef on __Type {
+  kind
+  name
+  ofType {
+    kind
+    name
+    ofType {
+      kind
+      name
+      ofType {
+        kind
+        name
+        ofType {
+          kind
+          name
+          ofType {
+            kind
+            name
+            ofType {
+              kind
+              name
+              ofType { kind name }
+            }
+          }
+        }
+      }
+    }
+  }
+}
+";
+
+/// The standard body of a GraphQL request over HTTP.
+#[derive(Serialize)]
+pub struct Operation<'a> {
+    pub query: &'a str,
+    #[serde(rename = "operationName", skip_serializing_if = "Option::is_none")]
+    pub operation_name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variables: Option<Value>,
+}
+
+/// The messages of the `errors` of a response, empty for a body that is not a
+/// GraphQL response.
+fn errors(body: &[u8]) -> Vec<String> {
+    let response: Value = match serde_json::from_slice(body) {
+        Ok(response) => response,
+        Err(_) => return Vec::new(),
+    };
+    match response.get("errors") {
+        Some(Value::Array(errors)) => errors
+            .iter()
+            .map(|e| match e.get("message") {
+                Some(Value::String(message)) => message.clone(),
+                _ => e.to_string(),
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Prints the errors of a response to stderr, failing with `fail`.
+pub fn check_errors(body: &[u8], fail: bool) -> Result<(), ExecutorError> {
+    let errors = errors(body);
+    for error in errors.iter() {
+        eprintln!("GraphQL error: {}", error);
+    }
+    match fail && !errors.is_empty() {
+        true => Err(ExecutorError::GraphQLErrors(errors.len())),
+        false => Ok(()),
+    }
+}
+
+/// Sends the introspection query to a GraphQL endpoint and saves the response
+/// to `output_file`, `API.ENDPOINT.schema.json` by default.
+pub fn introspect(
+    api: &str,
+    api_config: &APIConfig,
+    endpoint: &str,
+    context: &Option<String>,
+    inputs: &[(String, String)],
+    output_file: &Option<PathBuf>,
+) -> Result<(), ExecutorError> {
+    let api_endpoint = api_config.get_api_endpoint(endpoint).unwrap();
+    if api_endpoint.endpoint_type != APIEndpointType::GraphQL {
+        return Err(ExecutorError::NotGraphQL(
+            api.to_string(),
+            endpoint.to_string(),
+        ));
+    }
+    let inputs = match &api_endpoint.inputs {
+        Some(declared) => input::validate_inputs(endpoint, declared, inputs)?,
+        None => inputs.to_vec(),
+    };
+    let mut engine = Engine::new();
+    engine.set_graphql_query(INTROSPECTION_QUERY);
+    let (request, stream) = engine.stream(api, api_config, endpoint, context, &inputs)?;
+    let exchange = stream.into_exchange(request)?;
+    let status = exchange.response.status;
+    if !(200..300).contains(&status) {
+        return Err(ExecutorError::UnexpectedStatus(
+            status,
+            "a 2xx status".to_string(),
+        ));
+    }
+    check_errors(&exchange.response.body, true)?;
+    let schema: Value = serde_json::from_slice(&exchange.response.body)
+        .map_err(|e| ExecutorError::BodyNotJson(e.to_string()))?;
+    let default_path = PathBuf::from(format!("{}.{}.schema.json", api, endpoint));
+    let path: &Path = output_file.as_deref().unwrap_or(&default_path);
+    let json = serde_json::to_string_pretty(&schema)
+        .map_err(|e| ExecutorError::FailedToPrintBody(e.to_string()))?;
+    std::fs::write(path, json + "\n")
+        .map_err(|e| ExecutorError::FailedToWriteFile(path.display().to_string(), e.to_string()))?;
+    eprintln!("Saved the schema to {}", path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "./graphql_test.rs"]
+mod graphql_test;
@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use httpmock::Method::POST;
+    use httpmock::MockServer;
+    use serde_json::json;
+
+    use crate::cmd::config::{
+        APIBody, APIBodyType, APIConfig, APIContext, APIEndpoint, APIEndpointType, APIGraphQL,
+        APIMethod,
+    };
+    use crate::cmd::error::ExecutorError;
+    use crate::cmd::executor::Engine;
+    use crate::cmd::graphql::{check_errors, errors};
+
+    fn graphql_endpoint(variables: serde_json::Value) -> APIEndpoint {
+        APIEndpoint {
+            method: APIMethod::POST,
+            url: "http://{{url}}/graphql".to_string(),
+            endpoint_type: APIEndpointType::GraphQL,
+            graphql: Some(APIGraphQL {
+                query: APIBody::new(
+                    "query User($id: ID!) { user(id: $id) { name } }",
+                    APIBodyType::STRING,
+                ),
+                operation_name: Some("User".to_string()),
+                variables: Some(variables),
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn graphql_body_test() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/graphql")
+                .header("Content-Type", "application/json")
+                .json_body(json!({
+                    "query": "query User($id: ID!) { user(id: $id) { name } }",
+                    "operationName": "User",
+                    "variables": {"id": "42", "limit": 2}
+                }));
+            then.status(200)
+                .body(r#"{"data": null, "errors": [{"message": "not found"}]}"#);
+        });
+        let context: APIContext = HashMap::from([
+            ("url".to_string(), format!("localhost:{}", server.port())),
+            ("id".to_string(), "42".to_string()),
+        ]);
+        let endpoints = HashMap::from([
+            (
+                "map".to_string(),
+                graphql_endpoint(json!({"id": "{{id}}", "limit": 2})),
+            ),
+            (
+                "template".to_string(),
+                graphql_endpoint(json!(r#"{"id": "{{id}}", "limit": 2}"#)),
+            ),
+        ]);
+        let api_config = APIConfig::new(
+            Some(HashMap::from([("local".to_string(), context)])),
+            endpoints,
+        );
+        for endpoint in ["map", "template"] {
+            let (request, stream) = Engine::new()
+                .stream(
+                    "test",
+                    &api_config,
+                    endpoint,
+                    &Some("local".to_string()),
+                    &[],
+                )
+                .unwrap();
+            let exchange = stream.into_exchange(request).unwrap();
+            assert_eq!(errors(&exchange.response.body), vec!["not found"]);
+            assert!(check_errors(&exchange.response.body, false).is_ok());
+            assert!(matches!(
+                check_errors(&exchange.response.body, true),
+                Err(ExecutorError::GraphQLErrors(1))
+            ));
+        }
+        mock.assert_hits(2);
+    }
+
+    #[test]
+    fn graphql_nested_variables_test() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST).path("/graphql").json_body(json!({
+                "query": "query User($id: ID!) { user(id: $id) { name } }",
+                "operationName": "User",
+                "variables": {"filter": {"id": "42", "tags": ["acme", 1, {"team": "acme"}]}}
+            }));
+            then.status(200)
+                .body(r#"{"data": {"user": {"name": "john"}}}"#);
+        });
+        let context: APIContext = HashMap::from([
+            ("url".to_string(), format!("localhost:{}", server.port())),
+            ("id".to_string(), "42".to_string()),
+            ("tenant".to_string(), "acme".to_string()),
+        ]);
+        let variables =
+            json!({"filter": {"id": "{{id}}", "tags": ["{{tenant}}", 1, {"team": "{{tenant}}"}]}});
+        let endpoints = HashMap::from([
+            ("nested".to_string(), graphql_endpoint(variables)),
+            (
+                "broken".to_string(),
+                graphql_endpoint(json!({"filter": {"tags": ["{{missing}}"]}})),
+            ),
+        ]);
+        let api_config = APIConfig::new(
+            Some(HashMap::from([("local".to_string(), context)])),
+            endpoints,
+        );
+        let context = Some("local".to_string());
+        let (request, stream) = Engine::new()
+            .stream("test", &api_config, "nested", &context, &[])
+            .unwrap();
+        assert_eq!(stream.into_exchange(request).unwrap().response.status, 200);
+        mock.assert();
+        assert!(matches!(
+            Engine::new().stream("test", &api_config, "broken", &context, &[]),
+            Err(ExecutorError::FailedToResolveTemplate(_, _, field, _)) if field == "graphql variable filter.tags[0]"
+        ));
+    }
+}
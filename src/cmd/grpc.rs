@@ -0,0 +1,345 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use prost_reflect::{
+    prost::Message as _, prost_types::FileDescriptorProto, DescriptorPool, DynamicMessage,
+    MessageDescriptor, MethodDescriptor,
+};
+use protobuf::Message as _;
+use reqwest::blocking::Request;
+use tonic::{
+    client::Grpc,
+    codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder},
+    codegen::http::uri::PathAndQuery,
+    metadata::{AsciiMetadataKey, AsciiMetadataValue},
+    transport::{Channel, ClientTlsConfig, Endpoint},
+    Status,
+};
+use tonic_reflection::pb::{
+    server_reflection_client::ServerReflectionClient, server_reflection_request::MessageRequest,
+    server_reflection_response::MessageResponse, ServerReflectionRequest,
+};
+
+use super::{
+    config::APIGrpc,
+    error::ExecutorError,
+    executor::{Header, HttpResponse},
+};
+
+/// Headers that describe the HTTP request itself rather than the call, gRPC
+/// sets its own.
+const SKIPPED_HEADERS: [&str; 4] = ["content-type", "content-length", "te", "user-agent"];
+
+/// Encodes and decodes messages described at runtime.
+struct DynamicCodec {
+    response: MessageDescriptor,
+}
+
+struct DynamicEncoder;
+
+struct DynamicDecoder(MessageDescriptor);
+
+impl Codec for DynamicCodec {
+    type Encode = DynamicMessage;
+    type Decode = DynamicMessage;
+    type Encoder = DynamicEncoder;
+    type Decoder = DynamicDecoder;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        DynamicEncoder
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        DynamicDecoder(self.response.clone())
+    }
+}
+
+impl Encoder for DynamicEncoder {
+    type Item = DynamicMessage;
+    type Error = Status;
+
+    fn encode(&mut self, item: Self::Item, dst: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+        item.encode(dst)
+            .map_err(|e| Status::internal(e.to_string()))
+    }
+}
+
+impl Decoder for DynamicDecoder {
+    type Item = DynamicMessage;
+    type Error = Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+        DynamicMessage::decode(self.0.clone(), src)
+            .map(Some)
+            .map_err(|e| Status::internal(e.to_string()))
+    }
+}
+
+fn descriptor_error(e: impl ToString) -> ExecutorError {
+    ExecutorError::GrpcDescriptors(e.to_string())
+}
+
+/// The error followed by its causes, the transport errors of tonic only say
+/// what failed in their sources.
+fn error_chain(e: &dyn std::error::Error) -> String {
+    let mut message = e.to_string();
+    let mut source = e.source();
+    while let Some(cause) = source {
+        let cause_message = cause.to_string();
+        if !message.contains(&cause_message) {
+            message.push_str(&format!(": {}", cause_message));
+        }
+        source = cause.source();
+    }
+    message
+}
+
+fn transport_error(e: impl ToString) -> ExecutorError {
+    ExecutorError::HTTPRequestError(e.to_string())
+}
+
+/// Adds the files to the pool, each one after the files it imports.
+fn add_files(
+    pool: &mut DescriptorPool,
+    mut files: HashMap<String, FileDescriptorProto>,
+) -> Result<(), ExecutorError> {
+    fn add(
+        pool: &mut DescriptorPool,
+        name: &str,
+        files: &mut HashMap<String, FileDescriptorProto>,
+        added: &mut HashSet<String>,
+    ) -> Result<(), ExecutorError> {
+        let file = match files.remove(name) {
+            Some(file) => file,
+            None => return Ok(()),
+        };
+        added.insert(name.to_string());
+        for dependency in file.dependency.iter() {
+            if !added.contains(dependency) {
+                add(pool, dependency, files, added)?;
+            }
+        }
+        pool.add_file_descriptor_proto(file)
+            .map_err(descriptor_error)
+    }
+    let mut added = HashSet::new();
+    let mut names: Vec<String> = files.keys().cloned().collect();
+    names.sort();
+    for name in names {
+        add(pool, &name, &mut files, &mut added)?;
+    }
+    Ok(())
+}
+
+/// Parses the `.proto` files, relative paths are taken from the directory of
+/// the collection file defining them.
+fn load_protos(grpc: &APIGrpc, protos: &[PathBuf]) -> Result<DescriptorPool, ExecutorError> {
+    let base = grpc
+        .source
+        .as_deref()
+        .and_then(Path::parent)
+        .unwrap_or(Path::new(""));
+    let protos: Vec<PathBuf> = protos.iter().map(|p| base.join(p)).collect();
+    let import_paths: Vec<PathBuf> = match &grpc.import_paths {
+        Some(paths) => paths.iter().map(|p| base.join(p)).collect(),
+        None => protos
+            .iter()
+            .filter_map(|p| p.parent().map(Path::to_path_buf))
+            .collect(),
+    };
+    let parsed = protobuf_parse::Parser::new()
+        .pure()
+        .includes(import_paths)
+        .inputs(protos)
+        .parse_and_typecheck()
+        .map_err(|e| descriptor_error(format!("{:#}", e)))?;
+    let mut files = HashMap::new();
+    for file in parsed.file_descriptors {
+        let bytes = file.write_to_bytes().map_err(descriptor_error)?;
+        let file = FileDescriptorProto::decode(bytes.as_slice()).map_err(descriptor_error)?;
+        files.insert(file.name().to_string(), file);
+    }
+    let mut pool = DescriptorPool::new();
+    add_files(&mut pool, files)?;
+    Ok(pool)
+}
+
+/// Asks the server reflection for the file defining the service and for
+/// everything it imports.
+async fn reflect(channel: Channel, service: &str) -> Result<DescriptorPool, ExecutorError> {
+    let mut client = ServerReflectionClient::new(channel);
+    let mut files: HashMap<String, FileDescriptorProto> = HashMap::new();
+    let mut requested: HashSet<String> = HashSet::new();
+    let mut pending = vec![MessageRequest::FileContainingSymbol(service.to_string())];
+    while let Some(message_request) = pending.pop() {
+        let request = ServerReflectionRequest {
+            host: String::new(),
+            message_request: Some(message_request),
+        };
+        let mut responses = client
+            .server_reflection_info(tokio_stream::once(request))
+            .await
+            .map_err(|status| {
+                descriptor_error(format!("server reflection failed: {}", status.message()))
+            })?
+            .into_inner();
+        while let Some(response) = responses
+            .message()
+            .await
+            .map_err(|s| descriptor_error(s.message()))?
+        {
+            match response.message_response {
+                Some(MessageResponse::FileDescriptorResponse(response)) => {
+                    for bytes in response.file_descriptor_proto {
+                        let file = FileDescriptorProto::decode(bytes.as_slice())
+                            .map_err(descriptor_error)?;
+                        for dependency in file.dependency.iter() {
+                            if !files.contains_key(dependency)
+                                && requested.insert(dependency.clone())
+                            {
+                                pending.push(MessageRequest::FileByFilename(dependency.clone()));
+                            }
+                        }
+                        files.insert(file.name().to_string(), file);
+                    }
+                }
+                Some(MessageResponse::ErrorResponse(error)) => {
+                    return Err(descriptor_error(format!(
+                        "server reflection failed: {}",
+                        error.error_message
+                    )))
+                }
+                _ => (),
+            }
+        }
+    }
+    let mut pool = DescriptorPool::new();
+    add_files(&mut pool, files)?;
+    Ok(pool)
+}
+
+/// Splits `package.Service/Method`.
+fn split_method(method: &str) -> Result<(&str, &str), ExecutorError> {
+    method
+        .trim_start_matches('/')
+        .rsplit_once('/')
+        .ok_or_else(|| {
+            ExecutorError::GrpcMethodNotFound(
+                method.to_string(),
+                "expected `Service/Method`".to_string(),
+            )
+        })
+}
+
+fn find_method(pool: &DescriptorPool, method: &str) -> Result<MethodDescriptor, ExecutorError> {
+    let (service, name) = split_method(method)?;
+    let not_found =
+        |reason: &str| ExecutorError::GrpcMethodNotFound(method.to_string(), reason.to_string());
+    let service = pool
+        .get_service_by_name(service)
+        .ok_or_else(|| not_found("unknown service"))?;
+    let found = service
+        .methods()
+        .find(|m| m.name() == name)
+        .ok_or_else(|| not_found("unknown method"))?;
+    match found.is_client_streaming() || found.is_server_streaming() {
+        true => Err(not_found("only unary methods can be called")),
+        false => Ok(found),
+    }
+}
+
+async fn call_async(request: &Request, grpc: &APIGrpc) -> Result<HttpResponse, ExecutorError> {
+    let url = request.url();
+    let mut endpoint = Endpoint::from_shared(format!("{}://{}", url.scheme(), url.authority()))
+        .map_err(transport_error)?;
+    if url.scheme() == "https" {
+        endpoint = endpoint
+            .tls_config(ClientTlsConfig::new())
+            .map_err(transport_error)?;
+    }
+    let channel = endpoint
+        .connect()
+        .await
+        .map_err(|e| transport_error(error_chain(&e)))?;
+    let pool = match &grpc.protos {
+        Some(protos) if !protos.is_empty() => load_protos(grpc, protos)?,
+        _ => reflect(channel.clone(), split_method(&grpc.method)?.0).await?,
+    };
+    let method = find_method(&pool, &grpc.method)?;
+    let body = match request.body() {
+        Some(body) => body.as_bytes().ok_or_else(|| {
+            ExecutorError::InvalidGrpcMessage("the body is not in memory".to_string())
+        })?,
+        None => b"{}",
+    };
+    let mut deserializer = serde_json::Deserializer::from_slice(body);
+    let message = DynamicMessage::deserialize(method.input(), &mut deserializer)
+        .and_then(|message| deserializer.end().map(|_| message))
+        .map_err(|e| ExecutorError::InvalidGrpcMessage(e.to_string()))?;
+    let mut call = tonic::Request::new(message);
+    for (key, value) in request.headers().iter() {
+        if SKIPPED_HEADERS.contains(&key.as_str()) {
+            continue;
+        }
+        let key = AsciiMetadataKey::from_str(key.as_str()).map_err(transport_error)?;
+        let value = value
+            .to_str()
+            .map_err(transport_error)
+            .and_then(|v| AsciiMetadataValue::from_str(v).map_err(transport_error))?;
+        call.metadata_mut().insert(key, value);
+    }
+    let path = PathAndQuery::from_str(&format!(
+        "/{}/{}",
+        method.parent_service().full_name(),
+        method.name()
+    ))
+    .map_err(transport_error)?;
+    let mut client = Grpc::new(channel);
+    client.ready().await.map_err(transport_error)?;
+    let codec = DynamicCodec {
+        response: method.output(),
+    };
+    let response = client.unary(call, path, codec).await.map_err(|status| {
+        ExecutorError::GrpcStatus(format!("{:?}", status.code()), status.message().to_string())
+    })?;
+    let (metadata, message, _) = response.into_parts();
+    let mut headers: Vec<Header> = metadata
+        .into_headers()
+        .iter()
+        .map(|(key, value)| Header {
+            key: key.to_string(),
+            value: String::from_utf8_lossy(value.as_bytes()).into_owned(),
+        })
+        .collect();
+    headers.retain(|h| !h.key.eq_ignore_ascii_case("content-type"));
+    headers.push(Header {
+        key: "content-type".to_string(),
+        value: "application/json".to_string(),
+    });
+    let body =
+        serde_json::to_vec(&message).map_err(|e| ExecutorError::FailedToReadBody(e.to_string()))?;
+    Ok(HttpResponse {
+        status: 200,
+        version: "HTTP/2.0".to_string(),
+        headers,
+        body,
+    })
+}
+
+/// Calls the method with the JSON body of the request as the message and the
+/// headers as metadata. The reply is returned as a JSON response, a status
+/// other than OK is an error.
+pub fn call(request: &Request, grpc: &APIGrpc) -> Result<HttpResponse, ExecutorError> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(transport_error)?;
+    runtime.block_on(call_async(request, grpc))
+}
+
+#[cfg(test)]
+#[path = "./grpc_test.rs"]
+mod grpc_test;
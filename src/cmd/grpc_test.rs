@@ -0,0 +1,229 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::net::TcpListener;
+    use std::path::{Path, PathBuf};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    use clap::Parser;
+
+    use crate::cmd::config::{
+        APIBody, APIBodyType, APIConfig, APIContext, APIEndpoint, APIEndpointType, APIGrpc,
+        APIMethod,
+    };
+    use crate::cmd::error::ExecutorError;
+    use crate::cmd::executor::Engine;
+    use crate::cmd::parser::{CmdArgs, Commands, RunArgs};
+    use crate::cmd::run::run;
+
+    const HEALTH_PROTO: &str = r#"syntax = "proto3";
+package grpc.health.v1;
+message HealthCheckRequest { string service = 1; }
+message HealthCheckResponse {
+  enum ServingStatus { UNKNOWN = 0; SERVING = 1; NOT_SERVING = 2; SERVICE_UNKNOWN = 3; }
+  ServingStatus status = 1;
+}
+service Health { rpc Check(HealthCheckRequest) returns (HealthCheckResponse); }
+"#;
+
+    /// Starts a health service along with the server reflection, returning the
+    /// `x-request-id` metadata of every health call.
+    fn serve() -> (u16, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        listener.set_nonblocking(true).unwrap();
+        let request_ids = Arc::new(Mutex::new(Vec::new()));
+        let recorded = request_ids.clone();
+        // The error type is set by tonic.
+        #[allow(clippy::result_large_err)]
+        let record = move |request: tonic::Request<()>| {
+            let id = request
+                .metadata()
+                .get("x-request-id")
+                .map(|v| v.to_str().unwrap().to_string());
+            recorded.lock().unwrap().push(id.unwrap_or_default());
+            Ok(request)
+        };
+        thread::spawn(move || {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            runtime.block_on(async move {
+                let listener = tokio::net::TcpListener::from_std(listener).unwrap();
+                let (_reporter, health) = tonic_health::server::health_reporter();
+                let health = tonic::service::interceptor::InterceptedService::new(health, record);
+                let reflection = tonic_reflection::server::Builder::configure()
+                    .register_encoded_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET)
+                    .build()
+                    .unwrap();
+                tonic::transport::Server::builder()
+                    .add_service(health)
+                    .add_service(reflection)
+                    .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+                    .await
+                    .unwrap();
+            });
+        });
+        (port, request_ids)
+    }
+
+    /// An endpoint defined in `directory/health.yml`.
+    fn grpc_endpoint(directory: &Path, method: &str, protos: Option<Vec<PathBuf>>) -> APIEndpoint {
+        APIEndpoint {
+            method: APIMethod::POST,
+            url: "http://{{host}}".to_string(),
+            headers: Some(HashMap::from([(
+                "x-request-id".to_string(),
+                "{{id}}".to_string(),
+            )])),
+            body: Some(APIBody::new(
+                r#"{"service": "{{service}}"}"#,
+                APIBodyType::STRING,
+            )),
+            endpoint_type: APIEndpointType::Grpc,
+            grpc: Some(APIGrpc {
+                method: method.to_string(),
+                protos,
+                import_paths: None,
+                source: Some(directory.join("health.yml")),
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn grpc_call_test() {
+        let (port, request_ids) = serve();
+        let directory = std::env::temp_dir().join(format!("rbm-grpc-{}", port));
+        std::fs::create_dir_all(directory.join("protos")).unwrap();
+        std::fs::write(directory.join("protos/health.proto"), HEALTH_PROTO).unwrap();
+        let context: APIContext = HashMap::from([
+            ("host".to_string(), format!("127.0.0.1:{}", port)),
+            ("id".to_string(), "42".to_string()),
+            ("service".to_string(), "".to_string()),
+        ]);
+        let endpoints = HashMap::from([
+            (
+                "reflection".to_string(),
+                grpc_endpoint(&directory, "grpc.health.v1.Health/Check", None),
+            ),
+            (
+                "proto".to_string(),
+                grpc_endpoint(
+                    &directory,
+                    "grpc.health.v1.Health/Check",
+                    Some(vec![directory.join("protos/health.proto")]),
+                ),
+            ),
+            (
+                "relative".to_string(),
+                grpc_endpoint(
+                    &directory,
+                    "grpc.health.v1.Health/Check",
+                    Some(vec![PathBuf::from("protos/health.proto")]),
+                ),
+            ),
+            (
+                "unknown".to_string(),
+                grpc_endpoint(&directory, "grpc.health.v1.Health/Watch", None),
+            ),
+            (
+                "file".to_string(),
+                APIEndpoint {
+                    body: Some(APIBody::new(
+                        &directory.join("check.json").display().to_string(),
+                        APIBodyType::FILE,
+                    )),
+                    ..grpc_endpoint(&directory, "grpc.health.v1.Health/Check", None)
+                },
+            ),
+        ]);
+        std::fs::write(
+            directory.join("check.json"),
+            r#"{"service": "unknown.Service"}"#,
+        )
+        .unwrap();
+        let api_config = APIConfig::new(
+            Some(HashMap::from([("local".to_string(), context)])),
+            endpoints,
+        );
+        let local = Some("local".to_string());
+        for endpoint in ["reflection", "proto", "relative"] {
+            let exchange = Engine::new()
+                .call_grpc("test", &api_config, endpoint, &local, &[])
+                .unwrap();
+            assert_eq!(exchange.response.body, br#"{"status":"SERVING"}"#);
+        }
+        assert_eq!(*request_ids.lock().unwrap(), ["42", "42", "42"]);
+        let result = Engine::new().call_grpc("test", &api_config, "unknown", &local, &[]);
+        assert!(matches!(
+            result,
+            Err(ExecutorError::GrpcMethodNotFound(_, _))
+        ));
+        // The message is read from the file, the service it names is unknown.
+        let result = Engine::new().call_grpc("test", &api_config, "file", &local, &[]);
+        assert!(
+            matches!(result, Err(ExecutorError::GrpcStatus(_, _))),
+            "{:?}",
+            result.err()
+        );
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    fn run_args(args: &[&str]) -> RunArgs {
+        let command = [
+            "rbm",
+            "run",
+            "-a",
+            "test",
+            "-e",
+            "reflection",
+            "-c",
+            "local",
+        ];
+        match CmdArgs::parse_from(command.iter().chain(args)).command {
+            Commands::Run(args) => args,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn grpc_run_output_test() {
+        let (port, _) = serve();
+        let directory = std::env::temp_dir().join(format!("rbm-grpc-run-{}", port));
+        std::fs::create_dir_all(&directory).unwrap();
+        let context: APIContext = HashMap::from([
+            ("host".to_string(), format!("127.0.0.1:{}", port)),
+            ("id".to_string(), "7".to_string()),
+            ("service".to_string(), "".to_string()),
+        ]);
+        let endpoints = HashMap::from([(
+            "reflection".to_string(),
+            grpc_endpoint(&directory, "grpc.health.v1.Health/Check", None),
+        )]);
+        let api_config = APIConfig::new(
+            Some(HashMap::from([("local".to_string(), context)])),
+            endpoints,
+        );
+        let reply = directory.join("reply.json");
+        run(
+            &run_args(&["-o", reply.to_str().unwrap()]),
+            &api_config,
+            &directory,
+        )
+        .unwrap();
+        assert_eq!(std::fs::read(&reply).unwrap(), br#"{"status":"SERVING"}"#);
+        let replies = directory.join("replies");
+        run(
+            &run_args(&["--output-dir", replies.to_str().unwrap()]),
+            &api_config,
+            &directory,
+        )
+        .unwrap();
+        assert_eq!(std::fs::read_dir(&replies).unwrap().count(), 1);
+        assert!(matches!(
+            run(&run_args(&["--stream"]), &api_config, &directory),
+            Err(ExecutorError::OptionNotSupported(option, _)) if option == "--stream"
+        ));
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
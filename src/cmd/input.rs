@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+
+use regex::Regex;
+
+use super::{
+    config::{APIInput, APIInputType},
+    error::ExecutorError,
+};
+
+/// Checks the `-i key=value` pairs against the inputs declared by an endpoint
+/// and returns them with the declared defaults filled in.
+pub fn validate_inputs(
+    endpoint: &str,
+    declared: &HashMap<String, APIInput>,
+    inputs: &[(String, String)],
+) -> Result<Vec<(String, String)>, ExecutorError> {
+    for (key, value) in inputs.iter() {
+        let input = declared
+            .get(key)
+            .ok_or_else(|| ExecutorError::UnknownInput(endpoint.to_string(), key.clone()))?;
+        validate_value(key, input, value)?;
+    }
+    let mut result = inputs.to_vec();
+    let mut names: Vec<&String> = declared.keys().collect();
+    names.sort();
+    for name in names {
+        if inputs.iter().any(|(k, _)| k == name) {
+            continue;
+        }
+        let input = &declared[name];
+        match &input.default {
+            Some(default) => result.push((name.clone(), default.clone())),
+            None if input.required => {
+                return Result::Err(ExecutorError::MissingInput(
+                    endpoint.to_string(),
+                    name.clone(),
+                ))
+            }
+            None => (),
+        }
+    }
+    Result::Ok(result)
+}
+
+fn validate_value(key: &str, input: &APIInput, value: &str) -> Result<(), ExecutorError> {
+    check_value(input, value).map_err(|reason| ExecutorError::InvalidInput(key.to_string(), reason))
+}
+
+fn check_value(input: &APIInput, value: &str) -> Result<(), String> {
+    match input.input_type {
+        APIInputType::String => Ok(()),
+        APIInputType::Int => value
+            .parse::<i64>()
+            .map(|_| ())
+            .map_err(|_| format!("`{}` is not an int", value)),
+        APIInputType::Bool => match value {
+            "true" | "false" => Ok(()),
+            _ => Err(format!("`{}` is not a bool", value)),
+        },
+        APIInputType::Enum => {
+            let values = input
+                .values
+                .as_deref()
+                .ok_or("missing field `values` for an enum input")?;
+            if values.iter().any(|v| v == value) {
+                Ok(())
+            } else {
+                Err(format!("`{}` is not one of [{}]", value, values.join(", ")))
+            }
+        }
+        APIInputType::Regex => {
+            let pattern = input
+                .pattern
+                .as_deref()
+                .ok_or("missing field `pattern` for a regex input")?;
+            let regex =
+                Regex::new(pattern).map_err(|e| format!("bad pattern `{}`: {}", pattern, e))?;
+            if regex.is_match(value) {
+                Ok(())
+            } else {
+                Err(format!("`{}` does not match `{}`", value, pattern))
+            }
+        }
+    }
+}
+
+/// Checks that a declared input can be validated and that its default is a
+/// valid value.
+pub fn check_declaration(name: &str, input: &APIInput) -> Result<(), String> {
+    match input.input_type {
+        APIInputType::Enum if input.values.is_none() => {
+            return Err(format!(
+                "input {}: missing field `values` for an enum input",
+                name
+            ))
+        }
+        APIInputType::Regex => {
+            let pattern = input.pattern.as_deref().ok_or_else(|| {
+                format!("input {}: missing field `pattern` for a regex input", name)
+            })?;
+            Regex::new(pattern)
+                .map_err(|e| format!("input {}: bad pattern `{}`: {}", name, pattern, e))?;
+        }
+        _ => (),
+    }
+    match &input.default {
+        Some(default) => check_value(input, default)
+            .map_err(|reason| format!("input {}: default {}", name, reason)),
+        None => Ok(()),
+    }
+}
+
+/// One line summary of an input, e.g. `id (int, required) - The user id`.
+pub fn describe_input(name: &str, input: &APIInput) -> String {
+    let mut kind = input.input_type.to_string();
+    if let Some(values) = &input.values {
+        kind = format!("{}: {}", kind, values.join("|"));
+    }
+    if let Some(pattern) = &input.pattern {
+        kind = format!("{}: {}", kind, pattern);
+    }
+    if input.required {
+        kind.push_str(", required");
+    }
+    if let Some(default) = &input.default {
+        kind = format!("{}, default: {}", kind, default);
+    }
+    match &input.description {
+        Some(description) => format!("{} ({}) - {}", name, kind, description),
+        None => format!("{} ({})", name, kind),
+    }
+}
+
+#[cfg(test)]
+#[path = "./input_test.rs"]
+mod input_test;
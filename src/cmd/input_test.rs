@@ -0,0 +1,117 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::cmd::config::{APIInput, APIInputType};
+    use crate::cmd::error::ExecutorError;
+    use crate::cmd::input::{check_declaration, validate_inputs};
+
+    #[test]
+    fn fills_defaults_and_accepts_valid_values() {
+        let inputs = vec![
+            ("id".to_string(), "42".to_string()),
+            ("status".to_string(), "active".to_string()),
+        ];
+        let result = validate_inputs("users", &create_inputs(), &inputs).unwrap();
+        assert!(result.contains(&("slug".to_string(), "john".to_string())));
+        assert_eq!(result.len(), 3);
+    }
+
+    #[test]
+    fn rejects_unknown_missing_and_invalid_inputs() {
+        let declared = create_inputs();
+        let unknown = vec![
+            ("id".to_string(), "1".to_string()),
+            ("idd".to_string(), "1".to_string()),
+        ];
+        assert!(matches!(
+            validate_inputs("users", &declared, &unknown),
+            Err(ExecutorError::UnknownInput(_, key)) if key == "idd"
+        ));
+        assert!(matches!(
+            validate_inputs("users", &declared, &[]),
+            Err(ExecutorError::MissingInput(_, key)) if key == "id"
+        ));
+        for (key, value) in [("id", "one"), ("status", "gone"), ("slug", "John")] {
+            let inputs = vec![
+                ("id".to_string(), "1".to_string()),
+                (key.to_string(), value.to_string()),
+            ];
+            assert!(matches!(
+                validate_inputs("users", &declared, &inputs),
+                Err(ExecutorError::InvalidInput(k, _)) if k == key
+            ));
+        }
+    }
+
+    #[test]
+    fn checks_declarations() {
+        for (name, input) in create_inputs() {
+            assert!(check_declaration(&name, &input).is_ok());
+        }
+        let declared = create_inputs();
+        let id = |default: &str| APIInput {
+            default: Some(default.to_string()),
+            ..declared["id"].clone()
+        };
+        let slug = |pattern: Option<&str>, default: &str| APIInput {
+            pattern: pattern.map(String::from),
+            default: Some(default.to_string()),
+            ..declared["slug"].clone()
+        };
+        let status = APIInput {
+            values: None,
+            ..declared["status"].clone()
+        };
+        let cases = [
+            ("id", id("one"), "default `one` is not an int"),
+            ("slug", slug(None, "john"), "missing field `pattern`"),
+            ("slug", slug(Some("[a-z"), "john"), "bad pattern"),
+            ("slug", slug(Some("^[a-z]+$"), "John"), "does not match"),
+            ("status", status, "missing field `values`"),
+        ];
+        for (name, input, reason) in cases {
+            let error = check_declaration(name, &input).unwrap_err();
+            assert!(
+                error.starts_with(&format!("input {}:", name)) && error.contains(reason),
+                "{}",
+                error
+            );
+        }
+    }
+
+    fn create_inputs() -> HashMap<String, APIInput> {
+        let input = |input_type: APIInputType| APIInput {
+            input_type,
+            required: false,
+            default: None,
+            description: None,
+            values: None,
+            pattern: None,
+        };
+        HashMap::from([
+            (
+                "id".to_string(),
+                APIInput {
+                    required: true,
+                    ..input(APIInputType::Int)
+                },
+            ),
+            (
+                "status".to_string(),
+                APIInput {
+                    values: Some(vec!["active".to_string(), "disabled".to_string()]),
+                    ..input(APIInputType::Enum)
+                },
+            ),
+            (
+                "slug".to_string(),
+                APIInput {
+                    pattern: Some("^[a-z]+$".to_string()),
+                    default: Some("john".to_string()),
+                    ..input(APIInputType::Regex)
+                },
+            ),
+        ])
+    }
+}
@@ -0,0 +1,373 @@
+use std::{collections::HashMap, path::Path, str::FromStr};
+
+use regex::Regex;
+use reqwest::header::HeaderName;
+use serde::Serialize;
+
+use super::{
+    config::{APIBodyType, APICapture, APIConfig, APIEndpoint, APIFlow, APIMethod, AuthEndpoint},
+    error::{ConfigError, ExecutorError},
+    executor::CONTEXT_KEY,
+    filter::Filter,
+    flow::DEFAULT_ITEM,
+    parser::OutputFormat,
+    resolver::{self, Resolver},
+};
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Issue {
+    pub severity: Severity,
+    pub api: Option<String>,
+    pub endpoint: Option<String>,
+    pub field: Option<String>,
+    pub message: String,
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    errors: usize,
+    warnings: usize,
+    issues: &'a [Issue],
+}
+
+fn sorted_keys<V>(map: &HashMap<String, V>) -> Vec<&String> {
+    let mut keys: Vec<&String> = map.keys().collect();
+    keys.sort();
+    keys
+}
+
+/// Checks the loaded collections without sending any request, every load
+/// error is reported as an issue as well.
+pub fn lint(
+    apis: &HashMap<String, APIConfig>,
+    load_errors: &[ConfigError],
+    global_context: &HashMap<String, String>,
+) -> Vec<Issue> {
+    let mut issues: Vec<Issue> = load_errors
+        .iter()
+        .map(|e| Issue {
+            severity: Severity::Error,
+            api: None,
+            endpoint: None,
+            field: None,
+            message: e.to_string(),
+        })
+        .collect();
+    let resolver = resolver::new();
+    for api in sorted_keys(apis) {
+        let api_config = &apis[api];
+        let endpoints = api_config.get_api_endpoints();
+        for endpoint in sorted_keys(endpoints) {
+            let mut linter = EndpointLinter {
+                api,
+                endpoint,
+                global_context,
+                resolver: resolver.as_ref(),
+                issues: &mut issues,
+            };
+            linter.lint(api_config, &endpoints[endpoint]);
+        }
+        if let Some(flows) = api_config.get_flows() {
+            for flow in sorted_keys(flows) {
+                lint_flow(
+                    api,
+                    api_config,
+                    flow,
+                    &flows[flow],
+                    resolver.as_ref(),
+                    &mut issues,
+                );
+            }
+        }
+    }
+    issues
+}
+
+/// Problems of capture rules that would only show once a response comes.
+fn capture_issues(capture: &HashMap<String, APICapture>) -> Vec<(String, String)> {
+    let mut issues = Vec::new();
+    for name in sorted_keys(capture) {
+        let rule = &capture[name];
+        let field = format!("capture {}", name);
+        if rule.json.is_some() && rule.header.is_some() {
+            issues.push((
+                field.clone(),
+                "set either `json` or `header`, not both".to_string(),
+            ));
+        }
+        if let Some(Err(e)) = rule.json.as_deref().map(Filter::parse) {
+            issues.push((field.clone(), e.to_string()));
+        }
+        if let Some(Err(e)) = rule.regex.as_deref().map(Regex::new) {
+            issues.push((field, format!("invalid regex: {}", e)));
+        }
+    }
+    issues
+}
+
+fn lint_flow(
+    api: &str,
+    api_config: &APIConfig,
+    name: &str,
+    flow: &APIFlow,
+    resolver: &dyn Resolver,
+    issues: &mut Vec<Issue>,
+) {
+    let mut push = |step: usize, field: Option<String>, message: String| {
+        let field = match field {
+            Some(field) => format!("flow {} step {} {}", name, step, field),
+            None => format!("flow {} step {}", name, step),
+        };
+        issues.push(Issue {
+            severity: Severity::Error,
+            api: Some(api.to_string()),
+            endpoint: None,
+            field: Some(field),
+            message,
+        })
+    };
+    for (index, step) in flow.steps.iter().enumerate() {
+        let number = index + 1;
+        if !api_config.contains_endpoint(&step.endpoint) {
+            push(number, None, format!("unknown endpoint {}", step.endpoint));
+        }
+        let mut templates: Vec<(String, &String)> = step
+            .condition
+            .iter()
+            .map(|c| ("if".to_string(), c))
+            .collect();
+        if let Some(inputs) = &step.inputs {
+            for input in sorted_keys(inputs) {
+                templates.push((format!("input {}", input), &inputs[input]));
+            }
+        }
+        for (field, template) in templates {
+            if let Err(e) = resolver.variables(template) {
+                push(number, Some(field), e.to_string());
+            }
+        }
+        if let Some(capture) = &step.capture {
+            for (field, message) in capture_issues(capture) {
+                push(number, Some(field), message);
+            }
+        }
+    }
+}
+
+struct EndpointLinter<'a> {
+    api: &'a str,
+    endpoint: &'a str,
+    global_context: &'a HashMap<String, String>,
+    resolver: &'a dyn Resolver,
+    issues: &'a mut Vec<Issue>,
+}
+
+impl EndpointLinter<'_> {
+    fn push(&mut self, severity: Severity, field: &str, message: String) {
+        self.issues.push(Issue {
+            severity,
+            api: Some(self.api.to_string()),
+            endpoint: Some(self.endpoint.to_string()),
+            field: Some(field.to_string()),
+            message,
+        })
+    }
+
+    fn lint(&mut self, api_config: &APIConfig, endpoint: &APIEndpoint) {
+        let item = DEFAULT_ITEM.to_string();
+        let mut known: Vec<&String> = api_config
+            .get_api_contexts()
+            .map(|contexts| contexts.values().flat_map(|c| c.keys()).collect())
+            .unwrap_or_default();
+        known.extend(self.global_context.keys());
+        if let Some(inputs) = &endpoint.inputs {
+            known.extend(inputs.keys());
+        }
+        // Values captured by any endpoint or flow of the API end up in the
+        // session, flows also name the elements they iterate over.
+        for other in api_config.get_api_endpoints().values() {
+            known.extend(other.capture.iter().flat_map(|c| c.keys()));
+        }
+        for step in api_config
+            .get_flows()
+            .into_iter()
+            .flat_map(|f| f.values())
+            .flat_map(|f| f.steps.iter())
+        {
+            known.extend(step.capture.iter().flat_map(|c| c.keys()));
+            if step.for_each.is_some() {
+                known.push(step.item.as_ref().unwrap_or(&item));
+            }
+        }
+        // Without declared inputs any variable may still come from `-i`.
+        let unknown_severity = match endpoint.inputs {
+            Some(_) => Severity::Error,
+            None => Severity::Warning,
+        };
+
+        if let Some(filter) = endpoint.output.as_ref().and_then(|o| o.filter.as_ref()) {
+            if let Err(e) = Filter::parse(filter) {
+                self.push(Severity::Error, "output filter", e.to_string());
+            }
+        }
+
+        for status in endpoint.expect_status.iter().flatten() {
+            if !(100..600).contains(status) {
+                self.push(
+                    Severity::Error,
+                    "expect_status",
+                    format!("{} is not an HTTP status", status),
+                );
+            }
+        }
+
+        if let Some(grpc) = &endpoint.grpc {
+            let (service, method) = grpc
+                .method
+                .trim_start_matches('/')
+                .rsplit_once('/')
+                .unwrap_or_default();
+            if service.is_empty() || method.is_empty() {
+                self.push(
+                    Severity::Error,
+                    "grpc.method",
+                    format!(
+                        "`{}` is not of the form `package.Service/Method`",
+                        grpc.method
+                    ),
+                );
+            }
+        }
+
+        if let Some(capture) = &endpoint.capture {
+            for (field, message) in capture_issues(capture) {
+                self.push(Severity::Error, &field, message);
+            }
+            if matches!(endpoint.method, APIMethod::WS) || endpoint.stream.is_some() {
+                self.push(
+                    Severity::Error,
+                    "capture",
+                    "captures need the whole response, which WebSocket and streamed endpoints do not keep".to_string(),
+                );
+            }
+        }
+
+        let mut templates: Vec<(String, &String)> = vec![("url".to_string(), &endpoint.url)];
+        if let Some(headers) = &endpoint.headers {
+            for name in sorted_keys(headers) {
+                if let Err(e) = HeaderName::from_str(name) {
+                    self.push(
+                        Severity::Warning,
+                        &format!("header {}", name),
+                        format!("invalid header name: {}", e),
+                    );
+                }
+                templates.push((format!("header {}", name), &headers[name]));
+            }
+        }
+        if let Some(query) = &endpoint.query {
+            for name in sorted_keys(query) {
+                templates.push((format!("query {}", name), &query[name]));
+            }
+        }
+        if let Some(AuthEndpoint::Basic { username, password }) = &endpoint.auth {
+            templates.push(("auth username".to_string(), username));
+            templates.push(("auth password".to_string(), password));
+        }
+        if let Some(body) = &endpoint.body {
+            templates.push(("body".to_string(), &body.content));
+        }
+
+        for (field, template) in templates {
+            let variables = match self.resolver.variables(template) {
+                Ok(variables) => variables,
+                Err(e) => {
+                    self.push(Severity::Error, &field, e.to_string());
+                    continue;
+                }
+            };
+            for variable in variables.iter() {
+                if variable != CONTEXT_KEY && !known.contains(&variable) {
+                    self.push(
+                        unknown_severity,
+                        &field,
+                        format!(
+                            "unknown variable `{}`, no context or declared input defines it",
+                            variable
+                        ),
+                    );
+                }
+            }
+            let static_file = field == "body"
+                && variables.is_empty()
+                && matches!(
+                    endpoint.body.as_ref().map(|b| &b.api_body_type),
+                    Some(APIBodyType::FILE)
+                );
+            if static_file && !Path::new(template).exists() {
+                self.push(
+                    Severity::Warning,
+                    &field,
+                    format!("body file {} does not exist", template),
+                );
+            }
+        }
+    }
+}
+
+fn count(issues: &[Issue], severity: Severity) -> usize {
+    issues.iter().filter(|i| i.severity == severity).count()
+}
+
+/// Prints the issues and fails when there are errors, or warnings in strict mode.
+pub fn report(issues: &[Issue], format: &OutputFormat, strict: bool) -> Result<(), ExecutorError> {
+    let errors = count(issues, Severity::Error);
+    let warnings = count(issues, Severity::Warning);
+    match format {
+        OutputFormat::Text => {
+            for issue in issues.iter() {
+                let severity = match issue.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                };
+                match (&issue.api, &issue.endpoint, &issue.field) {
+                    (Some(api), Some(endpoint), Some(field)) => println!(
+                        "{}: {}.{} ({}): {}",
+                        severity, api, endpoint, field, issue.message
+                    ),
+                    (Some(api), None, Some(field)) => {
+                        println!("{}: {} ({}): {}", severity, api, field, issue.message)
+                    }
+                    _ => println!("{}: {}", severity, issue.message),
+                }
+            }
+            println!("{} error(s), {} warning(s)", errors, warnings);
+        }
+        OutputFormat::Json => {
+            let report = Report {
+                errors,
+                warnings,
+                issues,
+            };
+            let json = serde_json::to_string_pretty(&report)
+                .map_err(|e| ExecutorError::FailedToPrintBody(e.to_string()))?;
+            println!("{}", json);
+        }
+    }
+    let failures = if strict { errors + warnings } else { errors };
+    if failures > 0 {
+        return Result::Err(ExecutorError::ValidationFailed(failures));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "./lint_test.rs"]
+mod lint_test;
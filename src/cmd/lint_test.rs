@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::cmd::config::{APIConfig, APIContext, APIEndpoint};
+    use crate::cmd::error::ExecutorError;
+    use crate::cmd::lint::{lint, report, Severity};
+    use crate::cmd::parser::OutputFormat;
+
+    fn api(endpoints: &str) -> HashMap<String, APIConfig> {
+        let endpoints: HashMap<String, APIEndpoint> = serde_yaml::from_str(endpoints).unwrap();
+        let context: APIContext =
+            HashMap::from([("url".to_string(), "http://localhost".to_string())]);
+        let api_config = APIConfig::new(
+            Some(HashMap::from([("local".to_string(), context)])),
+            endpoints,
+        );
+        HashMap::from([("pets".to_string(), api_config)])
+    }
+
+    #[test]
+    fn lint_clean_test() {
+        let apis = api("get:\n  method: GET\n  url: \"{{url}}/pets\"\n  headers:\n    X-Tenant: \"{{tenant}}\"\n");
+        let global_context = HashMap::from([("tenant".to_string(), "acme".to_string())]);
+        let issues = lint(&apis, &[], &global_context);
+        assert!(issues.is_empty(), "{:?}", issues);
+        assert!(report(&issues, &OutputFormat::Json, true).is_ok());
+    }
+
+    #[test]
+    fn lint_issues_test() {
+        let apis = api(concat!(
+            "declared:\n  method: GET\n  url: \"{{url}}/pets/{{id}}\"\n  inputs:\n    name: {}\n",
+            "undeclared:\n  method: POST\n  url: \"{{url}}/pets\"\n  query:\n    q: \"{{search}}\"\n",
+            "  body: {type: file, content: missing-payload.json}\n",
+        ));
+        let issues = lint(&apis, &[], &HashMap::new());
+        let found: Vec<(Severity, &str, &str)> = issues
+            .iter()
+            .map(|i| {
+                (
+                    i.severity,
+                    i.endpoint.as_deref().unwrap(),
+                    i.field.as_deref().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (Severity::Error, "declared", "url"),
+                (Severity::Warning, "undeclared", "query q"),
+                (Severity::Warning, "undeclared", "body"),
+            ]
+        );
+        assert!(issues[0].message.contains("unknown variable `id`"));
+
+        let json = serde_json::to_value(&issues[2]).unwrap();
+        assert_eq!(json["severity"], "warning");
+        assert_eq!(json["api"], "pets");
+        assert!(json["message"].as_str().unwrap().contains("does not exist"));
+
+        assert!(matches!(
+            report(&issues, &OutputFormat::Text, false),
+            Err(ExecutorError::ValidationFailed(1))
+        ));
+        assert!(matches!(
+            report(&issues, &OutputFormat::Json, true),
+            Err(ExecutorError::ValidationFailed(3))
+        ));
+    }
+
+    #[test]
+    fn lint_capture_on_streams_test() {
+        let apis = api(concat!(
+            "socket:\n  method: WS\n  url: \"{{url}}/chat\"\n  capture:\n    token: {json: .token}\n",
+            "events:\n  method: GET\n  url: \"{{url}}/events\"\n  stream: sse\n  capture:\n    id: {json: .id}\n",
+        ));
+        let issues = lint(&apis, &[], &HashMap::new());
+        let found: Vec<(&str, &str)> = issues
+            .iter()
+            .map(|i| (i.endpoint.as_deref().unwrap(), i.field.as_deref().unwrap()))
+            .collect();
+        assert_eq!(found, [("events", "capture"), ("socket", "capture")]);
+        assert!(issues.iter().all(|i| i.severity == Severity::Error));
+    }
+}
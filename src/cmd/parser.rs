@@ -3,24 +3,204 @@ use std::path::PathBuf;
 use clap::{
     builder::TypedValueParser,
     error::{ContextKind, ContextValue},
-    Parser, Subcommand,
+    Args, Parser, Subcommand, ValueEnum,
 };
 use std::error::Error;
 
-use super::config::{self, Config};
+use super::{
+    config::{self, APIMethod, Config},
+    error::ConfigError,
+};
 
 #[derive(Parser, Debug)]
 pub struct CmdArgs {
     #[arg(short, long, value_name = "FILE")]
     config: Option<PathBuf>,
+    /// Keep going with the valid collection files when some fail to load
+    #[arg(long, global = true, default_value = "false")]
+    pub skip_invalid: bool,
     #[command(subcommand)]
     pub command: Commands,
 }
 
 #[derive(Subcommand, Debug)]
 pub enum Commands {
+    /// Create the config, a collection directory and an example collection
+    Init {
+        /// Create a `.rbm` project collection in the working directory instead
+        #[arg(long, default_value = "false")]
+        project: bool,
+    },
+    /// Add an API or an endpoint to a collection file
+    New {
+        #[command(subcommand)]
+        command: NewCommands,
+    },
+    /// Add, change or remove the contexts of an API
+    Context {
+        #[command(subcommand)]
+        command: ContextCommands,
+    },
+    /// Remove, rename, move or copy the endpoints of an API
+    Endpoint {
+        #[command(subcommand)]
+        command: EndpointCommands,
+    },
     List,
-    Run {
+    Describe {
+        #[arg(short = 'a', long)]
+        api: String,
+        #[arg(short = 'e', long)]
+        endpoint: String,
+    },
+    /// Check every collection without sending any request
+    Validate {
+        #[arg(short = 'f', long, value_enum, default_value = "text")]
+        format: OutputFormat,
+        /// Fail on warnings too
+        #[arg(long, default_value = "false")]
+        strict: bool,
+    },
+    /// Print the JSON Schema of the config or of a collection file
+    Schema {
+        #[arg(value_enum, default_value = "collection")]
+        kind: SchemaKind,
+    },
+    Run(RunArgs),
+    /// Work with `type: graphql` endpoints
+    Graphql {
+        #[command(subcommand)]
+        command: GraphQLCommands,
+    },
+    /// Run the `flows` of an API
+    Flow {
+        #[command(subcommand)]
+        command: FlowCommands,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct RunArgs {
+    #[arg(short = 'a', long)]
+    pub api: String,
+    #[arg(short = 'c', long)]
+    pub context: Option<String>,
+    #[arg(short = 'e', long)]
+    pub endpoint: String,
+    #[arg(short = 'i', long, value_parser = PairStringParser::new())]
+    pub input: Vec<(String, String)>,
+    #[arg(short = 'v', long, default_value = "false")]
+    pub verbose: bool,
+    /// Print the body as it was received, without pretty printing nor the
+    /// endpoint `output.filter`
+    #[arg(long, default_value = "false")]
+    pub raw: bool,
+    /// Extract part of a JSON body, e.g. `.items[].id` or `$.items[*].id`
+    #[arg(long)]
+    pub filter: Option<String>,
+    /// Exit with an error on a 4xx or 5xx response, or a GraphQL response
+    /// with errors
+    #[arg(long, default_value = "false")]
+    pub fail: bool,
+    /// Print the request, the response and the timing as one document
+    #[arg(long, value_enum, conflicts_with_all = ["filter", "raw"])]
+    pub output: Option<ExchangeFormat>,
+    #[arg(long, value_enum, default_value = "auto")]
+    pub color: ColorChoice,
+    /// Stream the body to a file instead of printing it
+    #[arg(short = 'o', long, value_name = "PATH", conflicts_with_all = ["filter", "output"])]
+    pub output_file: Option<PathBuf>,
+    /// Stream the body to a file named after the endpoint and the time in this
+    /// directory
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["output_file", "filter", "output"])]
+    pub output_dir: Option<PathBuf>,
+    /// Print the body as it arrives, for chunked and long running responses
+    #[arg(long, default_value = "false", conflicts_with_all = ["filter", "output", "output_file", "output_dir"])]
+    pub stream: bool,
+    /// Write the status line and the response headers to a file
+    #[arg(long, value_name = "PATH")]
+    pub dump_headers: Option<PathBuf>,
+    /// Send the lines typed on stdin as messages on a WebSocket endpoint
+    #[arg(long, default_value = "false")]
+    pub interactive: bool,
+    /// Stop reading an event stream or a WebSocket after this many events or messages
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub max_events: Option<u64>,
+    /// Stop reading an event stream or a WebSocket after this many seconds
+    #[arg(long, value_name = "SECS", value_parser = clap::value_parser!(u64).range(1..))]
+    pub max_time: Option<u64>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum NewCommands {
+    Api {
+        name: String,
+        /// Collection file to add the API to, `NAME.yml` in the first
+        /// collection directory by default
+        #[arg(short = 'f', long, value_name = "FILE")]
+        file: Option<PathBuf>,
+    },
+    Endpoint {
+        api: String,
+        name: String,
+        #[arg(
+            short = 'm',
+            long,
+            value_enum,
+            ignore_case = true,
+            default_value = "get"
+        )]
+        method: APIMethod,
+        #[arg(short = 'u', long)]
+        url: String,
+        #[arg(short = 'b', long)]
+        body: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ContextCommands {
+    /// Set values of a context, creating it when needed
+    Set {
+        api: String,
+        context: String,
+        #[arg(value_parser = PairStringParser::new(), required = true, value_name = "KEY=VALUE")]
+        values: Vec<(String, String)>,
+    },
+    /// Remove a context, or only the given keys of it
+    Rm {
+        api: String,
+        context: String,
+        keys: Vec<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum EndpointCommands {
+    /// Remove an endpoint of an API
+    Rm { api: String, name: String },
+    /// Rename an endpoint, or move it to another API
+    Mv {
+        api: String,
+        name: String,
+        new_name: String,
+        #[arg(long, value_name = "API")]
+        to_api: Option<String>,
+    },
+    /// Copy an endpoint under a new name, in the same API or another one
+    Cp {
+        api: String,
+        name: String,
+        new_name: String,
+        #[arg(long, value_name = "API")]
+        to_api: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum GraphQLCommands {
+    /// Save the schema of a GraphQL endpoint, fetched with the introspection query
+    Introspect {
         #[arg(short = 'a', long)]
         api: String,
         #[arg(short = 'c', long)]
@@ -29,10 +209,53 @@ pub enum Commands {
         endpoint: String,
         #[arg(short = 'i', long, value_parser = PairStringParser::new())]
         input: Vec<(String, String)>,
-        #[arg(short = 'v', long, default_value = "false")]
-        verbose: bool,
+        /// Defaults to `API.ENDPOINT.schema.json` in the working directory
+        #[arg(short = 'o', long, value_name = "PATH")]
+        output_file: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum FlowCommands {
+    /// Run the steps of a flow, printing a line per step
+    Run {
+        api: String,
+        flow: String,
+        #[arg(short = 'c', long)]
+        context: Option<String>,
+        /// Variables available to every step
+        #[arg(short = 'i', long, value_parser = PairStringParser::new())]
+        input: Vec<(String, String)>,
     },
 }
+
+#[derive(ValueEnum, Clone, Debug)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+pub enum ExchangeFormat {
+    Json,
+    Yaml,
+    /// One JSON object on a single line
+    Ndjson,
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+pub enum SchemaKind {
+    Config,
+    Collection,
+}
+
 #[derive(Copy, Clone, Debug)]
 struct PairStringParser {}
 
@@ -51,17 +274,14 @@ impl TypedValueParser for PairStringParser {
         _arg: Option<&clap::Arg>,
         value: &std::ffi::OsStr,
     ) -> Result<Self::Value, clap::Error> {
-        return value
-            .to_str()
-            .map(parse_key_val)
-            .unwrap_or_else(|| {
-                let mut err = clap::Error::new(clap::error::ErrorKind::InvalidValue);
-                err.insert(
-                    ContextKind::InvalidArg,
-                    ContextValue::String("argument cannot be empty".to_owned()),
-                );
-                Result::Err(err)
-            });
+        value.to_str().map(parse_key_val).unwrap_or_else(|| {
+            let mut err = clap::Error::new(clap::error::ErrorKind::InvalidValue);
+            err.insert(
+                ContextKind::InvalidArg,
+                ContextValue::String("argument cannot be empty".to_owned()),
+            );
+            Result::Err(err)
+        })
     }
 }
 
@@ -88,7 +308,15 @@ pub fn parse_cli_args() -> CmdArgs {
 }
 
 impl CmdArgs {
-    pub fn read_config(&self) -> Config {
+    /// The config file in use, given with `--config` or the default one.
+    pub fn config_file(&self) -> Result<PathBuf, ConfigError> {
+        match &self.config {
+            Some(file) => Ok(file.clone()),
+            None => config::default_config_file(),
+        }
+    }
+
+    pub fn read_config(&self) -> Result<Config, ConfigError> {
         config::read_config_or_create_default(&self.config)
     }
 }
@@ -0,0 +1,589 @@
+use std::io::{IsTerminal, Write};
+
+use percent_encoding::percent_decode_str;
+use serde::Serialize;
+
+use super::{
+    error::ExecutorError,
+    executor::{content_type, Exchange, Header, HttpResponse},
+    filter::Filter,
+    parser::{ColorChoice, ExchangeFormat},
+    sse::Event,
+    websocket::{Message, MessageKind},
+};
+
+const RESET: &str = "\x1b[0m";
+const BOLD_BLUE: &str = "\x1b[1;34m";
+const GREEN: &str = "\x1b[32m";
+const CYAN: &str = "\x1b[36m";
+const MAGENTA: &str = "\x1b[35m";
+const YELLOW: &str = "\x1b[33m";
+
+const INDENT: &str = "  ";
+const HEXDUMP_BYTES: usize = 256;
+/// HTML elements that never have a closing tag.
+const VOID_ELEMENTS: [&str; 14] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+/// Elements whose content is printed untouched.
+const RAW_TEXT_ELEMENTS: [&str; 4] = ["script", "style", "pre", "textarea"];
+
+/// How a body is shown, picked from its `Content-Type`.
+#[derive(Debug, PartialEq)]
+pub enum BodyKind {
+    Json,
+    Markup,
+    Form,
+    Text,
+    Binary,
+}
+
+pub struct Printer {
+    verbose: bool,
+    pretty: bool,
+    color: bool,
+    filter: Option<Filter>,
+}
+
+fn paint(text: &str, color: &str, enabled: bool) -> String {
+    match enabled {
+        true => format!("{}{}{}", color, text, RESET),
+        false => text.to_string(),
+    }
+}
+
+/// Guesses how to show a body, a body that is not UTF-8 is always binary.
+pub fn body_kind(content_type: Option<&str>, body: &[u8]) -> BodyKind {
+    if std::str::from_utf8(body).is_err() {
+        return BodyKind::Binary;
+    }
+    let essence = content_type
+        .and_then(|c| c.split(';').next())
+        .map(|c| c.trim().to_ascii_lowercase())
+        .unwrap_or_default();
+    match essence.as_str() {
+        "" => BodyKind::Text,
+        "application/x-www-form-urlencoded" => BodyKind::Form,
+        e if e.ends_with("/json") || e.ends_with("+json") => BodyKind::Json,
+        e if e.contains("xml") || e.contains("html") => BodyKind::Markup,
+        e if e.starts_with("text/")
+            || e.contains("javascript")
+            || e.contains("yaml")
+            || e.contains("csv") =>
+        {
+            BodyKind::Text
+        }
+        e if e.starts_with("image/")
+            || e.starts_with("audio/")
+            || e.starts_with("video/")
+            || e.starts_with("font/")
+            || e == "application/octet-stream"
+            || e == "application/pdf"
+            || e == "application/zip" =>
+        {
+            BodyKind::Binary
+        }
+        _ => BodyKind::Text,
+    }
+}
+
+fn next_significant(chars: &[char], from: usize) -> Option<char> {
+    chars[from..].iter().find(|c| !c.is_whitespace()).copied()
+}
+
+fn new_line(out: &mut String, depth: usize) {
+    out.push('\n');
+    out.push_str(&INDENT.repeat(depth));
+}
+
+/// Indents a JSON document token by token, which keeps the key order and the
+/// numbers exactly as they were sent.
+pub fn format_json(text: &str, color: bool) -> Option<String> {
+    serde_json::from_str::<serde::de::IgnoredAny>(text).ok()?;
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len() * 2);
+    let mut depth = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '"' => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && chars[i] != '"' {
+                    i += if chars[i] == '\\' { 2 } else { 1 };
+                }
+                i += 1;
+                let string: String = chars[start..i.min(chars.len())].iter().collect();
+                let is_key = next_significant(&chars, i) == Some(':');
+                out.push_str(&paint(
+                    &string,
+                    if is_key { BOLD_BLUE } else { GREEN },
+                    color,
+                ));
+            }
+            '{' | '[' => {
+                let close = if c == '{' { '}' } else { ']' };
+                i += 1;
+                if next_significant(&chars, i) == Some(close) {
+                    out.push(c);
+                    out.push(close);
+                    i += chars[i..].iter().position(|n| *n == close).unwrap() + 1;
+                } else {
+                    out.push(c);
+                    depth += 1;
+                    new_line(&mut out, depth);
+                }
+            }
+            '}' | ']' => {
+                depth = depth.saturating_sub(1);
+                new_line(&mut out, depth);
+                out.push(c);
+                i += 1;
+            }
+            ',' => {
+                out.push(',');
+                new_line(&mut out, depth);
+                i += 1;
+            }
+            ':' => {
+                out.push_str(": ");
+                i += 1;
+            }
+            _ => {
+                let start = i;
+                while i < chars.len() && !",:]}".contains(chars[i]) && !chars[i].is_whitespace() {
+                    i += 1;
+                }
+                let token: String = chars[start..i].iter().collect();
+                let token_color = match token.as_str() {
+                    "null" => MAGENTA,
+                    "true" | "false" => YELLOW,
+                    _ => CYAN,
+                };
+                out.push_str(&paint(&token, token_color, color));
+            }
+        }
+    }
+    Some(out)
+}
+
+enum MarkupToken<'a> {
+    Tag(&'a str),
+    Text(&'a str),
+}
+
+fn tag_name(tag: &str) -> String {
+    tag.trim_start_matches('<')
+        .trim_start_matches('/')
+        .split(|c: char| c.is_whitespace() || c == '>' || c == '/')
+        .next()
+        .unwrap_or("")
+        .to_ascii_lowercase()
+}
+
+fn tokenize_markup(text: &str) -> Vec<MarkupToken<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        if !rest.starts_with('<') {
+            let end = rest.find('<').unwrap_or(rest.len());
+            tokens.push(MarkupToken::Text(&rest[..end]));
+            rest = &rest[end..];
+            continue;
+        }
+        let terminator = match rest {
+            r if r.starts_with("<!--") => "-->",
+            r if r.starts_with("<![CDATA[") => "]]>",
+            _ => ">",
+        };
+        let end = rest
+            .find(terminator)
+            .map(|i| i + terminator.len())
+            .unwrap_or(rest.len());
+        let tag = &rest[..end];
+        tokens.push(MarkupToken::Tag(tag));
+        rest = &rest[end..];
+        let name = tag_name(tag);
+        if !tag.starts_with("</") && RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+            let close = format!("</{}", name);
+            let end = rest.to_ascii_lowercase().find(&close).unwrap_or(rest.len());
+            if end > 0 {
+                tokens.push(MarkupToken::Text(&rest[..end]));
+            }
+            rest = &rest[end..];
+        }
+    }
+    tokens
+}
+
+fn opens(tag: &str) -> bool {
+    !(tag.starts_with("</")
+        || tag.starts_with("<?")
+        || tag.starts_with("<!")
+        || tag.ends_with("/>")
+        || VOID_ELEMENTS.contains(&tag_name(tag).as_str()))
+}
+
+/// Indents XML and HTML, one tag per line, keeping an element whose only
+/// content is some text on a single line.
+pub fn format_markup(text: &str, color: bool) -> String {
+    let tokens = tokenize_markup(text);
+    let mut lines: Vec<String> = Vec::new();
+    let mut depth: usize = 0;
+    let mut i = 0;
+    while i < tokens.len() {
+        match tokens[i] {
+            MarkupToken::Text(text) => {
+                for line in text.lines().filter(|l| !l.trim().is_empty()) {
+                    lines.push(format!("{}{}", INDENT.repeat(depth), line.trim()));
+                }
+            }
+            MarkupToken::Tag(tag) if tag.starts_with("</") => {
+                depth = depth.saturating_sub(1);
+                lines.push(format!(
+                    "{}{}",
+                    INDENT.repeat(depth),
+                    paint(tag, CYAN, color)
+                ));
+            }
+            MarkupToken::Tag(tag) => {
+                if let (true, Some(MarkupToken::Text(text)), Some(MarkupToken::Tag(close))) =
+                    (opens(tag), tokens.get(i + 1), tokens.get(i + 2))
+                {
+                    if close.starts_with("</") && !text.trim().contains('\n') {
+                        lines.push(format!(
+                            "{}{}{}{}",
+                            INDENT.repeat(depth),
+                            paint(tag, CYAN, color),
+                            text.trim(),
+                            paint(close, CYAN, color)
+                        ));
+                        i += 3;
+                        continue;
+                    }
+                }
+                lines.push(format!(
+                    "{}{}",
+                    INDENT.repeat(depth),
+                    paint(tag, CYAN, color)
+                ));
+                if opens(tag) {
+                    depth += 1;
+                }
+            }
+        }
+        i += 1;
+    }
+    lines.join("\n")
+}
+
+fn decode_form_value(value: &str) -> String {
+    let value = value.replace('+', " ");
+    percent_decode_str(&value).decode_utf8_lossy().into_owned()
+}
+
+/// Prints url encoded form data as one decoded `key = value` per line.
+pub fn format_form(text: &str, color: bool) -> String {
+    text.trim()
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            format!(
+                "{} = {}",
+                paint(&decode_form_value(key), BOLD_BLUE, color),
+                decode_form_value(value)
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// A summary of a binary body with a hexdump of its first bytes.
+pub fn format_binary(content_type: Option<&str>, body: &[u8]) -> String {
+    let mut out = format!(
+        "Binary body, {} bytes of {}",
+        body.len(),
+        content_type.unwrap_or("unknown content type")
+    );
+    for (index, chunk) in body.chunks(16).take(HEXDUMP_BYTES / 16).enumerate() {
+        let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+        let ascii: String = chunk
+            .iter()
+            .map(|b| match b.is_ascii_graphic() || *b == b' ' {
+                true => *b as char,
+                false => '.',
+            })
+            .collect();
+        out.push_str(&format!(
+            "\n{:08x}  {:<47}  |{}|",
+            index * 16,
+            hex.join(" "),
+            ascii
+        ));
+    }
+    if body.len() > HEXDUMP_BYTES {
+        out.push_str(&format!(
+            "\n... {} more bytes, use --raw to print the whole body",
+            body.len() - HEXDUMP_BYTES
+        ));
+    }
+    out
+}
+
+/// The status line and the headers, followed by an empty line.
+pub fn format_head(status: u16, version: &str, headers: &[Header], color: bool) -> String {
+    let mut head = format!("{} {}\n", status, version);
+    for header in headers.iter() {
+        head.push_str(&format!(
+            "{}: {}\n",
+            paint(&header.key, BOLD_BLUE, color),
+            header.value
+        ));
+    }
+    head
+}
+
+impl Printer {
+    /// Pretty prints when stdout is a terminal unless `raw` is set, forcing the
+    /// colors on also pretty prints into a pipe.
+    pub fn new(verbose: bool, raw: bool, color: &ColorChoice) -> Printer {
+        let terminal = std::io::stdout().is_terminal();
+        let color = match color {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => terminal && std::env::var_os("NO_COLOR").is_none(),
+        };
+        Printer {
+            verbose,
+            pretty: !raw && (terminal || color),
+            color: !raw && color,
+            filter: None,
+        }
+    }
+
+    /// Prints what the filter yields from a JSON body instead of the body, one
+    /// JSON value per line unless pretty printing.
+    pub fn with_filter(mut self, filter: Option<Filter>) -> Printer {
+        self.filter = filter;
+        self
+    }
+
+    fn print_filtered(
+        &self,
+        out: &mut impl Write,
+        filter: &Filter,
+        body: &[u8],
+    ) -> Result<(), ExecutorError> {
+        let value: serde_json::Value =
+            serde_json::from_slice(body).map_err(|e| ExecutorError::BodyNotJson(e.to_string()))?;
+        for result in filter.apply(&value)? {
+            let text = serde_json::to_string(&result)
+                .map_err(|e| ExecutorError::FailedToPrintBody(e.to_string()))?;
+            let text = match self.pretty {
+                true => format_json(&text, self.color).unwrap_or(text),
+                false => text,
+            };
+            writeln!(out, "{}", text)
+                .map_err(|e| ExecutorError::FailedToPrintBody(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    fn format_body(&self, response: &HttpResponse) -> Option<String> {
+        let content_type = content_type(&response.headers);
+        let kind = body_kind(content_type, &response.body);
+        if kind == BodyKind::Binary {
+            return Some(format_binary(content_type, &response.body));
+        }
+        let text = std::str::from_utf8(&response.body).ok()?;
+        match kind {
+            BodyKind::Json => format_json(text, self.color),
+            BodyKind::Markup => Some(format_markup(text, self.color)),
+            BodyKind::Form => Some(format_form(text, self.color)),
+            BodyKind::Text | BodyKind::Binary => None,
+        }
+    }
+
+    /// Prints the whole exchange as a single document, see `Exchange`.
+    pub fn print_exchange(
+        &self,
+        exchange: &Exchange,
+        format: &ExchangeFormat,
+    ) -> Result<(), ExecutorError> {
+        let to_print_error = |err: String| ExecutorError::FailedToPrintBody(err);
+        let document = match format {
+            ExchangeFormat::Json => {
+                let json =
+                    serde_json::to_string(exchange).map_err(|e| to_print_error(e.to_string()))?;
+                match self.pretty {
+                    true => format_json(&json, self.color).unwrap_or(json),
+                    false => serde_json::to_string_pretty(exchange)
+                        .map_err(|e| to_print_error(e.to_string()))?,
+                }
+            }
+            ExchangeFormat::Yaml => serde_yaml::to_string(exchange)
+                .map(|yaml| yaml.trim_end().to_string())
+                .map_err(|e| to_print_error(e.to_string()))?,
+            ExchangeFormat::Ndjson => {
+                serde_json::to_string(exchange).map_err(|e| to_print_error(e.to_string()))?
+            }
+        };
+        let mut out = std::io::stdout();
+        writeln!(out, "{}", document).map_err(|e| to_print_error(e.to_string()))?;
+        out.flush().map_err(|e| to_print_error(e.to_string()))
+    }
+
+    /// Prints the status line and the headers in verbose mode.
+    pub fn print_head(
+        &self,
+        status: u16,
+        version: &str,
+        headers: &[Header],
+    ) -> Result<(), ExecutorError> {
+        if !self.verbose {
+            return Ok(());
+        }
+        let mut out = std::io::stdout();
+        writeln!(out, "{}", format_head(status, version, headers, self.color))
+            .and_then(|_| out.flush())
+            .map_err(|err| ExecutorError::FailedToPrintBody(err.to_string()))
+    }
+
+    /// One event or message in the format of `--output`, JSON stays on a
+    /// single line unless pretty printing.
+    fn format_record<T: Serialize>(
+        &self,
+        record: &T,
+        format: &ExchangeFormat,
+    ) -> Result<String, ExecutorError> {
+        let to_print_error = |err: String| ExecutorError::FailedToPrintBody(err);
+        match format {
+            ExchangeFormat::Yaml => serde_yaml::to_string(record)
+                .map(|yaml| format!("---\n{}", yaml.trim_end()))
+                .map_err(|e| to_print_error(e.to_string())),
+            ExchangeFormat::Json | ExchangeFormat::Ndjson => {
+                let json =
+                    serde_json::to_string(record).map_err(|e| to_print_error(e.to_string()))?;
+                match (format, self.pretty) {
+                    (ExchangeFormat::Json, true) => {
+                        Ok(format_json(&json, self.color).unwrap_or(json))
+                    }
+                    _ => Ok(json),
+                }
+            }
+        }
+    }
+
+    /// Prints an event as soon as it arrives, in the format of `--output` or
+    /// as its fields followed by an empty line. A filter applies to the data.
+    pub fn print_event(
+        &self,
+        event: &Event,
+        format: Option<&ExchangeFormat>,
+    ) -> Result<(), ExecutorError> {
+        let mut out = std::io::stdout();
+        let to_print_error =
+            |err: std::io::Error| ExecutorError::FailedToPrintBody(err.to_string());
+        let text = match (format, &self.filter) {
+            (Some(format), _) => self.format_record(event, format)?,
+            (None, Some(filter)) => {
+                self.print_filtered(&mut out, filter, event.data.as_bytes())?;
+                return out.flush().map_err(to_print_error);
+            }
+            (None, None) => {
+                let mut text = String::new();
+                if event.event != "message" {
+                    text.push_str(&format!(
+                        "{}: {}\n",
+                        paint("event", BOLD_BLUE, self.color),
+                        event.event
+                    ));
+                }
+                if let Some(id) = &event.id {
+                    text.push_str(&format!("{}: {}\n", paint("id", BOLD_BLUE, self.color), id));
+                }
+                let data = match self.pretty {
+                    true => format_json(&event.data, self.color),
+                    false => None,
+                };
+                text.push_str(data.as_deref().unwrap_or(&event.data));
+                text.push('\n');
+                text
+            }
+        };
+        writeln!(out, "{}", text)
+            .and_then(|_| out.flush())
+            .map_err(to_print_error)
+    }
+
+    /// Prints a WebSocket message as soon as it arrives, in the format of
+    /// `--output` or as a body on its own line. A filter applies to text messages.
+    pub fn print_message(
+        &self,
+        message: &Message,
+        format: Option<&ExchangeFormat>,
+    ) -> Result<(), ExecutorError> {
+        let mut out = std::io::stdout();
+        let to_print_error =
+            |err: std::io::Error| ExecutorError::FailedToPrintBody(err.to_string());
+        match (format, &self.filter, &message.kind) {
+            (Some(format), _, _) => {
+                writeln!(out, "{}", self.format_record(message, format)?).map_err(to_print_error)?
+            }
+            (None, Some(filter), MessageKind::Text) => {
+                self.print_filtered(&mut out, filter, &message.data)?
+            }
+            (None, _, MessageKind::Binary) if self.pretty => {
+                writeln!(out, "{}", format_binary(None, &message.data)).map_err(to_print_error)?
+            }
+            (None, _, _) => {
+                let text = match self.pretty {
+                    true => std::str::from_utf8(&message.data)
+                        .ok()
+                        .and_then(|t| format_json(t, self.color)),
+                    false => None,
+                };
+                match text {
+                    Some(text) => writeln!(out, "{}", text),
+                    None => out.write_all(&message.data).and_then(|_| writeln!(out)),
+                }
+                .map_err(to_print_error)?
+            }
+        }
+        out.flush().map_err(to_print_error)
+    }
+
+    pub fn print(&self, http_response: &HttpResponse) -> Result<(), ExecutorError> {
+        let mut out = std::io::stdout();
+        let to_print_error =
+            |err: std::io::Error| ExecutorError::FailedToPrintBody(err.to_string());
+        self.print_head(
+            http_response.status,
+            &http_response.version,
+            &http_response.headers,
+        )?;
+        if let Some(filter) = &self.filter {
+            self.print_filtered(&mut out, filter, &http_response.body)?;
+        } else if !http_response.body.is_empty() {
+            match self
+                .pretty
+                .then(|| self.format_body(http_response))
+                .flatten()
+            {
+                Some(formatted) => writeln!(out, "{}", formatted).map_err(to_print_error)?,
+                None => out
+                    .write_all(http_response.body.as_slice())
+                    .map_err(to_print_error)?,
+            }
+        }
+        out.flush().map_err(to_print_error)
+    }
+}
+
+#[cfg(test)]
+#[path = "./printer_test.rs"]
+mod printer_test;
@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod tests {
+    use crate::cmd::printer::{
+        body_kind, format_binary, format_form, format_json, format_markup, BodyKind,
+    };
+
+    #[test]
+    fn body_kind_test() {
+        assert_eq!(
+            body_kind(Some("application/json; charset=utf-8"), b"{}"),
+            BodyKind::Json
+        );
+        assert_eq!(
+            body_kind(Some("application/problem+json"), b"{}"),
+            BodyKind::Json
+        );
+        assert_eq!(body_kind(Some("text/html"), b"<p/>"), BodyKind::Markup);
+        assert_eq!(
+            body_kind(Some("application/x-www-form-urlencoded"), b"a=1"),
+            BodyKind::Form
+        );
+        assert_eq!(body_kind(Some("image/png"), b"PNG"), BodyKind::Binary);
+        assert_eq!(
+            body_kind(Some("text/plain"), &[0xff, 0xfe]),
+            BodyKind::Binary
+        );
+        assert_eq!(body_kind(None, b"hello"), BodyKind::Text);
+    }
+
+    #[test]
+    fn format_bodies_test() {
+        assert_eq!(
+            format_json(r#"{"b":1.50,"a":[true,null,"x\"y"],"e":{}}"#, false).unwrap(),
+            "{\n  \"b\": 1.50,\n  \"a\": [\n    true,\n    null,\n    \"x\\\"y\"\n  ],\n  \"e\": {}\n}"
+        );
+        assert_eq!(format_json("{\"a\":", false), None);
+        assert_eq!(
+            format_json("[1]", true).unwrap(),
+            "[\n  \x1b[36m1\x1b[0m\n]"
+        );
+        assert_eq!(
+            format_markup("<?xml version=\"1.0\"?><a><b>text</b><br><c x=\"1\"/><script>if (a<b) {}</script></a>", false),
+            "<?xml version=\"1.0\"?>\n<a>\n  <b>text</b>\n  <br>\n  <c x=\"1\"/>\n  <script>if (a<b) {}</script>\n</a>"
+        );
+        assert_eq!(
+            format_form("name=John+Doe&city=S%C3%A3o", false),
+            "name = John Doe\ncity = São"
+        );
+        let binary = format_binary(Some("image/png"), &[0x89, b'P', b'N', b'G']);
+        assert_eq!(
+            binary,
+            format!(
+                "Binary body, 4 bytes of image/png\n00000000  89 50 4e 47{}  |.PNG|",
+                " ".repeat(36)
+            )
+        );
+    }
+}
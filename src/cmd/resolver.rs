@@ -1,8 +1,21 @@
-use tera::{Context, Tera};
+mod functions;
+mod variables;
+
+use std::{collections::BTreeSet, error::Error};
+
+use regex::Regex;
+use tera::{Context, Template, Tera};
+
+use super::error::ResolverError;
+
+const ONE_OFF_TEMPLATE: &str = " while rendering '__tera_one_off'";
 
 pub trait Resolver {
-    fn resolve(&mut self, tpl: &str) -> String;
+    fn resolve(&mut self, tpl: &str) -> Result<String, ResolverError>;
     fn add_context(&mut self, key: String, value: &str);
+    /// Parses a template without rendering it and returns the context
+    /// variables it reads.
+    fn variables(&self, tpl: &str) -> Result<BTreeSet<String>, ResolverError>;
 }
 
 struct TeraResolver {
@@ -11,20 +24,54 @@ struct TeraResolver {
 }
 
 pub fn new() -> Box<dyn Resolver> {
+    let mut tera = Tera::default();
+    functions::register(&mut tera);
     Box::new(TeraResolver {
-        tera: Tera::default(),
+        tera,
         context: Context::new(),
     })
 }
 
 impl Resolver for TeraResolver {
-    fn resolve(&mut self, tpl: &str) -> String {
-        self
-            .tera
+    fn resolve(&mut self, tpl: &str) -> Result<String, ResolverError> {
+        self.tera
             .render_str(tpl, &self.context)
-            .expect("failed to resolve template")
+            .map_err(to_resolver_error)
     }
     fn add_context(&mut self, key: String, value: &str) {
         self.context.insert(key, value);
     }
+    fn variables(&self, tpl: &str) -> Result<BTreeSet<String>, ResolverError> {
+        let template = Template::new("__tera_one_off", None, tpl).map_err(to_resolver_error)?;
+        Ok(variables::collect(&template.ast))
+    }
 }
+
+/// Tera wraps the interesting part of an error in its sources, the top level
+/// message only says that the one off template failed to parse or render.
+fn to_resolver_error(err: tera::Error) -> ResolverError {
+    let mut messages: Vec<String> = Vec::new();
+    let mut source = err.source();
+    while let Some(cause) = source {
+        messages.push(cause.to_string().replace(ONE_OFF_TEMPLATE, ""));
+        source = cause.source();
+    }
+    if messages.is_empty() {
+        messages.push(err.to_string());
+    }
+    let syntax = Regex::new(r"(?s)-->\s*(\d+):(\d+).*=\s*(.*)$").unwrap();
+    for message in messages.iter() {
+        if let Some(captures) = syntax.captures(message) {
+            return ResolverError::Syntax(
+                captures[1].parse().unwrap_or_default(),
+                captures[2].parse().unwrap_or_default(),
+                captures[3].trim().to_string(),
+            );
+        }
+    }
+    ResolverError::Render(messages.join(": "))
+}
+
+#[cfg(test)]
+#[path = "./resolver_test.rs"]
+mod resolver_test;
@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{
+    format::{Item, StrftimeItems},
+    Local, Utc,
+};
+use hmac::{Hmac, Mac};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use rand::{distributions::Alphanumeric, seq::SliceRandom, Rng};
+use sha2::{Digest, Sha256};
+use tera::{Error, Result, Tera, Value};
+
+const FIRST_NAMES: [&str; 10] = [
+    "Alice", "Bruno", "Carla", "Daniel", "Elena", "Filipe", "Grace", "Hugo", "Ines", "Jonas",
+];
+const LAST_NAMES: [&str; 10] = [
+    "Almeida", "Brown", "Costa", "Dubois", "Evans", "Ferreira", "Garcia", "Hansen", "Ito", "Jensen",
+];
+
+/// Registers the rbm specific functions and filters on top of the Tera builtins
+/// (`json_encode`, `urlencode`, `date`, ...).
+pub fn register(tera: &mut Tera) {
+    tera.register_function("uuid", uuid);
+    tera.register_function("now", now);
+    tera.register_function("random_int", random_int);
+    tera.register_function("random_string", random_string);
+    tera.register_function("file", file);
+    tera.register_function("fake_name", fake_name);
+    tera.register_function("fake_email", fake_email);
+    tera.register_filter("base64_encode", base64_encode);
+    tera.register_filter("base64_decode", base64_decode);
+    tera.register_filter("url_encode", url_encode);
+    tera.register_filter("sha256", sha256);
+    tera.register_filter("hmac", hmac);
+}
+
+fn arg<'a>(args: &'a HashMap<String, Value>, name: &str) -> Option<&'a Value> {
+    args.get(name).filter(|v| !v.is_null())
+}
+
+fn str_arg<'a>(args: &'a HashMap<String, Value>, name: &str) -> Result<Option<&'a str>> {
+    match arg(args, name) {
+        None => Ok(None),
+        Some(Value::String(s)) => Ok(Some(s)),
+        Some(other) => Err(Error::msg(format!(
+            "`{}` must be a string, got {}",
+            name, other
+        ))),
+    }
+}
+
+fn int_arg(args: &HashMap<String, Value>, name: &str, default: i64) -> Result<i64> {
+    match arg(args, name) {
+        None => Ok(default),
+        Some(v) => v
+            .as_i64()
+            .ok_or_else(|| Error::msg(format!("`{}` must be an integer, got {}", name, v))),
+    }
+}
+
+fn bool_arg(args: &HashMap<String, Value>, name: &str) -> Result<bool> {
+    match arg(args, name) {
+        None => Ok(false),
+        Some(v) => v
+            .as_bool()
+            .ok_or_else(|| Error::msg(format!("`{}` must be a boolean, got {}", name, v))),
+    }
+}
+
+fn as_str<'a>(value: &'a Value, filter: &str) -> Result<&'a str> {
+    value.as_str().ok_or_else(|| {
+        Error::msg(format!(
+            "filter `{}` expects a string, got {}",
+            filter, value
+        ))
+    })
+}
+
+fn encode(bytes: &[u8], args: &HashMap<String, Value>) -> Result<Value> {
+    match str_arg(args, "encoding")?.unwrap_or("hex") {
+        "hex" => Ok(Value::String(hex::encode(bytes))),
+        "base64" => Ok(Value::String(STANDARD.encode(bytes))),
+        other => Err(Error::msg(format!(
+            "unsupported encoding `{}`, use hex or base64",
+            other
+        ))),
+    }
+}
+
+/// `uuid()` - a random v4 UUID.
+fn uuid(_args: &HashMap<String, Value>) -> Result<Value> {
+    Ok(Value::String(uuid::Uuid::new_v4().to_string()))
+}
+
+/// `now(format="%Y-%m-%d", utc=true, timestamp=false)` - the current time,
+/// RFC 3339 formatted unless a strftime `format` is given.
+fn now(args: &HashMap<String, Value>) -> Result<Value> {
+    if bool_arg(args, "timestamp")? {
+        return Ok(Value::from(Utc::now().timestamp()));
+    }
+    let format = str_arg(args, "format")?;
+    // chrono panics while printing a format with an unknown specifier.
+    if let Some(format) = format {
+        if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+            return Err(Error::msg(format!("now: invalid format `{}`", format)));
+        }
+    }
+    let formatted = match (bool_arg(args, "utc")?, format) {
+        (true, Some(format)) => Utc::now().format(format).to_string(),
+        (true, None) => Utc::now().to_rfc3339(),
+        (false, Some(format)) => Local::now().format(format).to_string(),
+        (false, None) => Local::now().to_rfc3339(),
+    };
+    Ok(Value::String(formatted))
+}
+
+/// `random_int(start=0, end=100)` - a random integer in `[start, end)`.
+fn random_int(args: &HashMap<String, Value>) -> Result<Value> {
+    let start = int_arg(args, "start", 0)?;
+    let end = int_arg(args, "end", 100)?;
+    if start >= end {
+        return Err(Error::msg(format!(
+            "random_int: start {} must be lower than end {}",
+            start, end
+        )));
+    }
+    Ok(Value::from(rand::thread_rng().gen_range(start..end)))
+}
+
+/// `random_string(length=16)` - a random alphanumeric string.
+fn random_string(args: &HashMap<String, Value>) -> Result<Value> {
+    let length = int_arg(args, "length", 16)?;
+    let value: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(length.max(0) as usize)
+        .map(char::from)
+        .collect();
+    Ok(Value::String(value))
+}
+
+/// `file(path="payload.json")` - the contents of a file.
+fn file(args: &HashMap<String, Value>) -> Result<Value> {
+    let path = str_arg(args, "path")?.ok_or_else(|| Error::msg("file: missing `path` argument"))?;
+    std::fs::read_to_string(path)
+        .map(Value::String)
+        .map_err(|e| Error::msg(format!("file: could not read {}: {}", path, e)))
+}
+
+fn pick(names: &[&'static str]) -> &'static str {
+    names.choose(&mut rand::thread_rng()).unwrap()
+}
+
+/// `fake_name()` - a random full name.
+fn fake_name(_args: &HashMap<String, Value>) -> Result<Value> {
+    Ok(Value::String(format!(
+        "{} {}",
+        pick(&FIRST_NAMES),
+        pick(&LAST_NAMES)
+    )))
+}
+
+/// `fake_email(domain="example.com")` - a random, unique looking email address.
+fn fake_email(args: &HashMap<String, Value>) -> Result<Value> {
+    let domain = str_arg(args, "domain")?.unwrap_or("example.com");
+    let email = format!(
+        "{}.{}{}@{}",
+        pick(&FIRST_NAMES).to_lowercase(),
+        pick(&LAST_NAMES).to_lowercase(),
+        rand::thread_rng().gen_range(0..10000),
+        domain
+    );
+    Ok(Value::String(email))
+}
+
+fn base64_encode(value: &Value, _args: &HashMap<String, Value>) -> Result<Value> {
+    Ok(Value::String(
+        STANDARD.encode(as_str(value, "base64_encode")?),
+    ))
+}
+
+fn base64_decode(value: &Value, _args: &HashMap<String, Value>) -> Result<Value> {
+    let bytes = STANDARD
+        .decode(as_str(value, "base64_decode")?)
+        .map_err(|e| Error::msg(format!("base64_decode: {}", e)))?;
+    String::from_utf8(bytes)
+        .map(Value::String)
+        .map_err(|e| Error::msg(format!("base64_decode: {}", e)))
+}
+
+fn url_encode(value: &Value, _args: &HashMap<String, Value>) -> Result<Value> {
+    let encoded = utf8_percent_encode(as_str(value, "url_encode")?, NON_ALPHANUMERIC);
+    Ok(Value::String(encoded.to_string()))
+}
+
+/// `{{ body | sha256(encoding="hex") }}`
+fn sha256(value: &Value, args: &HashMap<String, Value>) -> Result<Value> {
+    encode(&Sha256::digest(as_str(value, "sha256")?), args)
+}
+
+/// `{{ body | hmac(key="secret", encoding="base64") }}` - HMAC-SHA256.
+fn hmac(value: &Value, args: &HashMap<String, Value>) -> Result<Value> {
+    let key = str_arg(args, "key")?.ok_or_else(|| Error::msg("hmac: missing `key` argument"))?;
+    let mut mac = Hmac::<Sha256>::new_from_slice(key.as_bytes())
+        .map_err(|e| Error::msg(format!("hmac: {}", e)))?;
+    mac.update(as_str(value, "hmac")?.as_bytes());
+    encode(&mac.finalize().into_bytes(), args)
+}
@@ -0,0 +1,114 @@
+use std::collections::{BTreeSet, HashSet};
+
+use tera::ast::{Expr, ExprVal, FunctionCall, Node};
+
+/// Collects the context variables a template reads, leaving out the ones it
+/// defines itself with `set` or `for`, the ones guarded by a `default` filter
+/// and the ones only checked with a test such as `is defined`.
+pub fn collect(nodes: &[Node]) -> BTreeSet<String> {
+    let mut variables = BTreeSet::new();
+    visit_nodes(nodes, &mut HashSet::new(), &mut variables);
+    variables
+}
+
+fn root(ident: &str) -> &str {
+    ident.split(['.', '[']).next().unwrap_or(ident)
+}
+
+fn visit_nodes(nodes: &[Node], locals: &mut HashSet<String>, variables: &mut BTreeSet<String>) {
+    for node in nodes {
+        match node {
+            Node::VariableBlock(_, expr) => visit_expr(expr, locals, variables),
+            Node::Set(_, set) => {
+                visit_expr(&set.value, locals, variables);
+                locals.insert(set.key.clone());
+            }
+            Node::FilterSection(_, section, _) => {
+                visit_call(&section.filter, locals, variables);
+                visit_nodes(&section.body, locals, variables);
+            }
+            Node::Block(_, block, _) => visit_nodes(&block.body, locals, variables),
+            Node::Forloop(_, forloop, _) => {
+                visit_expr(&forloop.container, locals, variables);
+                let mut loop_locals = locals.clone();
+                loop_locals.insert("loop".to_string());
+                loop_locals.insert(forloop.value.clone());
+                if let Some(key) = &forloop.key {
+                    loop_locals.insert(key.clone());
+                }
+                visit_nodes(&forloop.body, &mut loop_locals, variables);
+                if let Some(empty_body) = &forloop.empty_body {
+                    visit_nodes(empty_body, locals, variables);
+                }
+            }
+            Node::If(condition, _) => {
+                for (_, expr, body) in condition.conditions.iter() {
+                    visit_expr(expr, locals, variables);
+                    visit_nodes(body, locals, variables);
+                }
+                if let Some((_, body)) = &condition.otherwise {
+                    visit_nodes(body, locals, variables);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+fn visit_call(call: &FunctionCall, locals: &HashSet<String>, variables: &mut BTreeSet<String>) {
+    for arg in call.args.values() {
+        visit_expr(arg, locals, variables);
+    }
+}
+
+fn visit_expr(expr: &Expr, locals: &HashSet<String>, variables: &mut BTreeSet<String>) {
+    for filter in expr.filters.iter() {
+        visit_call(filter, locals, variables);
+    }
+    match &expr.val {
+        ExprVal::Ident(ident) => {
+            let name = root(ident);
+            if !expr.has_default_filter() && !locals.contains(name) {
+                variables.insert(name.to_string());
+            }
+        }
+        ExprVal::Math(math) => {
+            visit_expr(&math.lhs, locals, variables);
+            visit_expr(&math.rhs, locals, variables);
+        }
+        ExprVal::Logic(logic) => {
+            visit_expr(&logic.lhs, locals, variables);
+            visit_expr(&logic.rhs, locals, variables);
+        }
+        ExprVal::In(contains) => {
+            visit_expr(&contains.lhs, locals, variables);
+            visit_expr(&contains.rhs, locals, variables);
+        }
+        ExprVal::Test(test) => {
+            for arg in test.args.iter() {
+                visit_expr(arg, locals, variables);
+            }
+        }
+        ExprVal::FunctionCall(call) => visit_call(call, locals, variables),
+        ExprVal::MacroCall(call) => {
+            for arg in call.args.values() {
+                visit_expr(arg, locals, variables);
+            }
+        }
+        ExprVal::Array(values) => {
+            for value in values.iter() {
+                visit_expr(value, locals, variables);
+            }
+        }
+        ExprVal::StringConcat(concat) => {
+            for value in concat.values.iter() {
+                if let ExprVal::Ident(ident) = value {
+                    if !locals.contains(root(ident)) {
+                        variables.insert(root(ident).to_string());
+                    }
+                }
+            }
+        }
+        ExprVal::String(_) | ExprVal::Int(_) | ExprVal::Float(_) | ExprVal::Bool(_) => (),
+    }
+}
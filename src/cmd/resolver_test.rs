@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod tests {
+    use crate::cmd::error::ResolverError;
+    use crate::cmd::resolver;
+
+    #[test]
+    fn builtin_functions_test() {
+        let mut resolver = resolver::new();
+        resolver.add_context("payload".to_string(), "hello");
+        assert_eq!(
+            resolver.resolve("{{ payload | base64_encode }}").unwrap(),
+            "aGVsbG8="
+        );
+        assert_eq!(
+            resolver
+                .resolve("{{ 'aGVsbG8=' | base64_decode }}")
+                .unwrap(),
+            "hello"
+        );
+        assert_eq!(
+            resolver.resolve("{{ 'a b&c' | url_encode }}").unwrap(),
+            "a%20b%26c"
+        );
+        assert_eq!(
+            resolver.resolve("{{ payload | sha256 }}").unwrap(),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        assert_eq!(
+            resolver
+                .resolve("{{ payload | hmac(key='secret') }}")
+                .unwrap(),
+            "88aab3ede8d3adf94d26ab90d3bafd4a2083070c3bcce9c014ee04a443847c0b"
+        );
+        assert_eq!(resolver.resolve("{{ uuid() }}").unwrap().len(), 36);
+        assert_eq!(
+            resolver
+                .resolve("{{ random_string(length=8) }}")
+                .unwrap()
+                .len(),
+            8
+        );
+        let number: i64 = resolver
+            .resolve("{{ random_int(start=5, end=6) }}")
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(number, 5);
+        assert_eq!(
+            resolver
+                .resolve("{{ now(format='%Y', utc=true) }}")
+                .unwrap()
+                .len(),
+            4
+        );
+        assert!(resolver
+            .resolve("{{ fake_email(domain='test.io') }}")
+            .unwrap()
+            .ends_with("@test.io"));
+    }
+
+    #[test]
+    fn template_errors_test() {
+        let mut resolver = resolver::new();
+        assert!(matches!(
+            resolver.resolve("http://{{url}}\n/{{ id | }}"),
+            Err(ResolverError::Syntax(2, 10, _))
+        ));
+        match resolver.resolve("http://{{url}}/get") {
+            Err(ResolverError::Render(message)) => {
+                assert_eq!(message, "Variable `url` not found in context")
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        match resolver.resolve("{{ now(format='%Q') }}") {
+            Err(ResolverError::Render(message)) => {
+                assert!(message.contains("invalid format `%Q`"), "{}", message)
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn template_variables_test() {
+        let resolver = resolver::new();
+        let variables = resolver
+            .variables(
+                "{{ host }}/{% set v = version %}{{ v }}{% for item in items %}{{ item.id }}{{ loop.index }}{% endfor %}\
+                 {{ token | default(value='none') }}{% if user.admin %}{{ sha(key=secret) }}{% endif %}",
+            )
+            .unwrap();
+        let variables: Vec<&str> = variables.iter().map(|v| v.as_str()).collect();
+        assert_eq!(variables, ["host", "items", "secret", "user", "version"]);
+        assert!(matches!(
+            resolver.variables("{{ a | }}"),
+            Err(ResolverError::Syntax(1, 8, _))
+        ));
+    }
+}
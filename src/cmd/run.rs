@@ -0,0 +1,321 @@
+use std::{
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use super::{
+    capture,
+    config::{APIConfig, APIEndpoint, APIEndpointType, APIMethod, APIStream},
+    error::ExecutorError,
+    executor::{content_type, Engine, Header, HttpResponse, ResponseStream, EVENT_STREAM},
+    filter::Filter,
+    graphql, input,
+    parser::RunArgs,
+    printer::{format_head, Printer},
+    sse::{self, Event},
+    stream::{self, Chunks, Limits},
+    websocket::{self, Message},
+};
+
+const CHUNK_SIZE: usize = 8192;
+
+/// Fails when the status is not one the endpoint expects, or with `--fail`
+/// when it is a client or server error.
+pub fn check_status(
+    status: u16,
+    expected: &Option<Vec<u16>>,
+    fail: bool,
+) -> Result<(), ExecutorError> {
+    match expected {
+        Some(expected) if !expected.contains(&status) => {
+            let expected: Vec<String> = expected.iter().map(|s| s.to_string()).collect();
+            Result::Err(ExecutorError::UnexpectedStatus(status, expected.join(", ")))
+        }
+        None if fail && status >= 400 => Result::Err(ExecutorError::UnexpectedStatus(
+            status,
+            "a status below 400".to_string(),
+        )),
+        _ => Result::Ok(()),
+    }
+}
+
+fn write_error(path: &Path, e: std::io::Error) -> ExecutorError {
+    ExecutorError::FailedToWriteFile(path.display().to_string(), e.to_string())
+}
+
+fn dump_headers(
+    path: &Path,
+    status: u16,
+    version: &str,
+    headers: &[Header],
+) -> Result<(), ExecutorError> {
+    std::fs::write(path, format_head(status, version, headers, false))
+        .map_err(|e| write_error(path, e))
+}
+
+/// A file name made of the API, the endpoint and the time, with an extension
+/// guessed from the content type.
+fn output_file_name(api: &str, endpoint: &str, content_type: Option<&str>) -> String {
+    let essence = content_type
+        .and_then(|c| c.split(';').next())
+        .map(|c| c.trim().to_ascii_lowercase())
+        .unwrap_or_default();
+    let extension = match essence.as_str() {
+        e if e.ends_with("json") => "json",
+        e if e.ends_with("html") => "html",
+        e if e.ends_with("xml") => "xml",
+        "text/csv" => "csv",
+        e if e.starts_with("text/") => "txt",
+        e => match e.split_once('/') {
+            Some(("image" | "audio" | "video", subtype))
+                if subtype.chars().all(char::is_alphanumeric) =>
+            {
+                subtype
+            }
+            Some((_, "pdf" | "zip")) => &e[e.len() - 3..],
+            _ => "bin",
+        },
+    };
+    let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S%.3f");
+    format!("{}.{}-{}.{}", api, endpoint, timestamp, extension)
+}
+
+/// Copies the body to a file as it arrives.
+fn save_body(stream: &mut ResponseStream, path: &Path) -> Result<(), ExecutorError> {
+    let mut file = File::create(path).map_err(|e| write_error(path, e))?;
+    std::io::copy(stream, &mut file).map_err(|e| write_error(path, e))?;
+    file.flush().map_err(|e| write_error(path, e))
+}
+
+/// Copies the body to stdout chunk by chunk as it arrives, until it ends or
+/// Ctrl-C is pressed.
+fn stream_to_stdout(stream: ResponseStream) -> Result<(), ExecutorError> {
+    let mut out = std::io::stdout();
+    let to_print_error = |e: std::io::Error| ExecutorError::FailedToPrintBody(e.to_string());
+    let copy = |chunk: Vec<u8>| {
+        out.write_all(&chunk)
+            .and_then(|_| out.flush())
+            .map_err(to_print_error)?;
+        Ok(true)
+    };
+    stream::forward(Chunks::new(stream, CHUNK_SIZE), copy, None)?;
+    Ok(())
+}
+
+/// The file the body goes to with `--output-file` or `--output-dir`.
+fn destination(args: &RunArgs, headers: &[Header]) -> Result<Option<PathBuf>, ExecutorError> {
+    match (&args.output_file, &args.output_dir) {
+        (Some(path), _) => Ok(Some(path.clone())),
+        (None, Some(directory)) => {
+            std::fs::create_dir_all(directory).map_err(|e| write_error(directory, e))?;
+            let name = output_file_name(&args.api, &args.endpoint, content_type(headers));
+            let path = directory.join(name);
+            eprintln!("Saving the body to {}", path.display());
+            Ok(Some(path))
+        }
+        (None, None) => Ok(None),
+    }
+}
+
+fn limits(args: &RunArgs) -> Limits {
+    Limits {
+        max_events: args.max_events,
+        deadline: args
+            .max_time
+            .map(|secs| Instant::now() + Duration::from_secs(secs)),
+    }
+}
+
+/// Reads a `text/event-stream` response event by event, reconnecting with the
+/// last event ID until the server answers 204 or a limit is reached.
+fn listen_events(
+    engine: &mut Engine,
+    args: &RunArgs,
+    api_config: &APIConfig,
+    input: &[(String, String)],
+    stream: ResponseStream,
+    printer: &Printer,
+) -> Result<(), ExecutorError> {
+    let RunArgs { api, endpoint, .. } = args;
+    let reconnect = |last_id: Option<&str>| {
+        if let Some(id) = last_id {
+            engine.set_header("Last-Event-ID", id)?;
+        }
+        let (_, stream) = engine.stream(api, api_config, endpoint, &args.context, input)?;
+        match stream.status {
+            204 => Ok(None),
+            200..=299 => Ok(Some(stream)),
+            status => Err(ExecutorError::UnexpectedStatus(
+                status,
+                "a 2xx status".to_string(),
+            )),
+        }
+    };
+    let on_event = |event: &Event| printer.print_event(event, args.output.as_ref());
+    sse::listen(stream, reconnect, on_event, &limits(args))
+}
+
+/// Connects to a WebSocket endpoint, sends the body as the first message and
+/// prints the messages received, sending the lines of stdin with `--interactive`.
+fn run_websocket(
+    engine: &mut Engine,
+    args: &RunArgs,
+    api_config: &APIConfig,
+    input: &[(String, String)],
+    printer: &Printer,
+) -> Result<u16, ExecutorError> {
+    let RunArgs { api, endpoint, .. } = args;
+    let (mut socket, first_message) =
+        engine.connect(api, api_config, endpoint, &args.context, input)?;
+    printer.print_head(socket.status, &socket.version, &socket.headers)?;
+    if let Some(path) = &args.dump_headers {
+        dump_headers(path, socket.status, &socket.version, &socket.headers)?;
+    }
+    if let Some(message) = first_message.filter(|m| !m.is_empty()) {
+        socket.send(message)?;
+    }
+    let lines = args.interactive.then(websocket::stdin_lines);
+    let on_message = |message: &Message| printer.print_message(message, args.output.as_ref());
+    socket.listen(lines, on_message, &limits(args))?;
+    Ok(socket.status)
+}
+
+/// Saves what the endpoint captures from a 2xx response to the session.
+fn save_captures(
+    api_endpoint: &APIEndpoint,
+    response: &HttpResponse,
+    session_file: &Path,
+) -> Result<(), ExecutorError> {
+    match &api_endpoint.capture {
+        Some(rules) if (200..300).contains(&response.status) => {
+            capture::save(session_file, capture::capture(rules, response)?)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Why the response of a run cannot be captured from, as the streaming paths
+/// never hold it in full.
+fn capture_not_supported(
+    api_endpoint: &APIEndpoint,
+    args: &RunArgs,
+    raw_stream: bool,
+) -> Option<ExecutorError> {
+    api_endpoint.capture.as_ref()?;
+    let reason = match api_endpoint.method {
+        APIMethod::WS => "a WebSocket",
+        _ if raw_stream => "a streamed body",
+        _ if args.output_file.is_some() || args.output_dir.is_some() => "a body saved to a file",
+        _ if api_endpoint.stream == Some(APIStream::Sse) => "an event stream",
+        _ => return None,
+    };
+    Some(ExecutorError::CaptureNotSupported(reason.to_string()))
+}
+
+fn is_event_stream(headers: &[Header]) -> bool {
+    content_type(headers)
+        .and_then(|c| c.split(';').next())
+        .map(|c| c.trim().eq_ignore_ascii_case(EVENT_STREAM))
+        .unwrap_or(false)
+}
+
+/// Runs an endpoint with the values captured earlier in the same API and
+/// context, saving the ones it captures in `session_directory`.
+pub fn run(
+    args: &RunArgs,
+    api_config: &APIConfig,
+    session_directory: &Path,
+) -> Result<(), ExecutorError> {
+    let RunArgs { api, endpoint, .. } = args;
+    let api_endpoint = api_config.get_api_endpoint(endpoint).unwrap();
+    let default_filter = match args.raw || args.output.is_some() {
+        true => None,
+        false => api_endpoint.output.as_ref().and_then(|o| o.filter.as_ref()),
+    };
+    let filter = args
+        .filter
+        .as_ref()
+        .or(default_filter)
+        .map(|f| Filter::parse(f))
+        .transpose()?;
+    let input = match &api_endpoint.inputs {
+        Some(declared) => input::validate_inputs(endpoint, declared, &args.input)?,
+        None => args.input.clone(),
+    };
+    let session_file = capture::session_file(session_directory, api, &args.context);
+    let mut engine = Engine::new();
+    engine.add_session(&capture::load(&session_file)?);
+    let printer = Printer::new(args.verbose, args.raw, &args.color).with_filter(filter);
+    if api_endpoint.endpoint_type == APIEndpointType::Grpc {
+        if args.stream {
+            return Err(ExecutorError::OptionNotSupported(
+                "--stream".to_string(),
+                "a gRPC call".to_string(),
+            ));
+        }
+        let exchange = engine.call_grpc(api, api_config, endpoint, &args.context, &input)?;
+        let response = &exchange.response;
+        if let Some(path) = &args.dump_headers {
+            dump_headers(path, response.status, &response.version, &response.headers)?;
+        }
+        match (destination(args, &response.headers)?, &args.output) {
+            (Some(path), _) => {
+                printer.print_head(response.status, &response.version, &response.headers)?;
+                std::fs::write(&path, &response.body).map_err(|e| write_error(&path, e))?
+            }
+            (None, Some(format)) => printer.print_exchange(&exchange, format)?,
+            (None, None) => printer.print(response)?,
+        }
+        return save_captures(api_endpoint, &exchange.response, &session_file);
+    }
+    let raw_stream = args.stream || api_endpoint.stream == Some(APIStream::Raw);
+    if let Some(e) = capture_not_supported(api_endpoint, args, raw_stream) {
+        return Err(e);
+    }
+    if let APIMethod::WS = api_endpoint.method {
+        let status = run_websocket(&mut engine, args, api_config, &input, &printer)?;
+        return check_status(status, &api_endpoint.expect_status, args.fail);
+    }
+    let (request, mut stream) = engine.stream(api, api_config, endpoint, &args.context, &input)?;
+    let status = stream.status;
+    let events = !raw_stream
+        && (200..300).contains(&status)
+        && (api_endpoint.stream == Some(APIStream::Sse) || is_event_stream(&stream.headers));
+    if events && api_endpoint.capture.is_some() {
+        return Err(ExecutorError::CaptureNotSupported(
+            "an event stream".to_string(),
+        ));
+    }
+    let destination = destination(args, &stream.headers)?;
+    if let Some(path) = &args.dump_headers {
+        dump_headers(path, status, &stream.version, &stream.headers)?;
+    }
+    if raw_stream || destination.is_some() || events {
+        printer.print_head(status, &stream.version, &stream.headers)?;
+        match destination {
+            Some(path) => save_body(&mut stream, &path)?,
+            None if events => {
+                listen_events(&mut engine, args, api_config, &input, stream, &printer)?
+            }
+            None => stream_to_stdout(stream)?,
+        }
+    } else {
+        let exchange = stream.into_exchange(request)?;
+        match &args.output {
+            Some(format) => printer.print_exchange(&exchange, format)?,
+            None => printer.print(&exchange.response)?,
+        }
+        if api_endpoint.endpoint_type == APIEndpointType::GraphQL {
+            graphql::check_errors(&exchange.response.body, args.fail)?;
+        }
+        check_status(status, &api_endpoint.expect_status, args.fail)?;
+        return save_captures(api_endpoint, &exchange.response, &session_file);
+    }
+    check_status(status, &api_endpoint.expect_status, args.fail)
+}
+
+#[cfg(test)]
+#[path = "./run_test.rs"]
+mod run_test;
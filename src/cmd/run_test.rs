@@ -0,0 +1,74 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use clap::Parser;
+
+    use crate::cmd::config::{APICapture, APIConfig, APIEndpoint, APIStream};
+    use crate::cmd::error::ExecutorError;
+    use crate::cmd::parser::{CmdArgs, Commands};
+    use crate::cmd::run::{check_status, run};
+
+    #[test]
+    fn check_status_test() {
+        assert!(check_status(200, &None, true).is_ok());
+        assert!(check_status(404, &None, false).is_ok());
+        assert!(matches!(
+            check_status(404, &None, true),
+            Err(ExecutorError::UnexpectedStatus(404, expected)) if expected == "a status below 400"
+        ));
+        assert!(matches!(
+            check_status(503, &None, true),
+            Err(ExecutorError::UnexpectedStatus(503, _))
+        ));
+    }
+
+    #[test]
+    fn check_expected_status_test() {
+        let expected = Some(vec![201, 404]);
+        assert!(check_status(201, &expected, false).is_ok());
+        // An expected error status passes even with `--fail`.
+        assert!(check_status(404, &expected, true).is_ok());
+        for fail in [false, true] {
+            assert!(matches!(
+                check_status(200, &expected, fail),
+                Err(ExecutorError::UnexpectedStatus(200, expected)) if expected == "201, 404"
+            ));
+        }
+    }
+
+    #[test]
+    fn streams_cannot_capture_test() {
+        let endpoint = |stream: Option<APIStream>| APIEndpoint {
+            // Nothing listens there, the run must stop before connecting.
+            url: "http://127.0.0.1:9/login".to_string(),
+            stream,
+            capture: Some(HashMap::from([(
+                "token".to_string(),
+                APICapture {
+                    json: Some("$.token".to_string()),
+                    header: None,
+                    regex: None,
+                },
+            )])),
+            ..Default::default()
+        };
+        let endpoints = HashMap::from([
+            ("login".to_string(), endpoint(None)),
+            ("raw".to_string(), endpoint(Some(APIStream::Raw))),
+        ]);
+        let api_config = APIConfig::new(None, endpoints);
+        let session_directory =
+            std::env::temp_dir().join(format!("rbm-run-stream-{}", std::process::id()));
+        for args in [["-e", "login", "--stream"].as_slice(), &["-e", "raw"]] {
+            let command = ["rbm", "run", "-a", "test"].iter().chain(args);
+            let Commands::Run(args) = CmdArgs::parse_from(command).command else {
+                unreachable!()
+            };
+            assert!(matches!(
+                run(&args, &api_config, &session_directory),
+                Err(ExecutorError::CaptureNotSupported(reason)) if reason == "a streamed body"
+            ));
+        }
+    }
+}
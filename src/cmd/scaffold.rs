@@ -0,0 +1,171 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use super::{
+    config::{
+        self,
+        editor::{scalar, YamlDocument},
+        APIConfig, APIMethod, Config, PROJECT_DIRECTORY,
+    },
+    error::{ConfigError, ExecutorError},
+};
+
+const EXAMPLE_FILE: &str = "example.yml";
+
+const EXAMPLE_COLLECTION: &str = r#"# An example collection, each file in a collection directory holds one or more
+# APIs. Try it with:
+#
+#   rbm run -a example -e get -c default -i name=rbm
+#
+# `rbm validate` checks every collection and `rbm schema` prints the JSON
+# Schema of these files for editor completion.
+example:
+  # Variables of the templates, pick one with `-c`.
+  context:
+    default:
+      url: https://httpbin.org
+  # Settings shared by every endpoint.
+  defaults:
+    headers:
+      Accept: application/json
+  endpoints:
+    get:
+      method: GET
+      url: "{{url}}/get"
+      query:
+        name: "{{name}}"
+      # Values given with `-i key=value`.
+      inputs:
+        name:
+          default: world
+          description: Name sent in the query
+    post:
+      # Everything left out is taken from `get`.
+      extends: get
+      method: POST
+      url: "{{url}}/post"
+      headers:
+        Content-Type: application/json
+      body: |
+        {"name": "{{name}}", "id": "{{ uuid() }}"}
+"#;
+
+fn create_directory(directory: &Path) -> Result<(), ConfigError> {
+    std::fs::create_dir_all(directory)
+        .map_err(|e| ConfigError::Write(directory.to_path_buf(), e.to_string()))
+}
+
+fn write_example(directory: &Path) -> Result<(), ConfigError> {
+    create_directory(directory)?;
+    let example = directory.join(EXAMPLE_FILE);
+    if example.exists() {
+        println!("Keeping {}", example.display());
+        return Ok(());
+    }
+    std::fs::write(&example, EXAMPLE_COLLECTION)
+        .map_err(|e| ConfigError::Write(example.clone(), e.to_string()))?;
+    println!("Created {}", example.display());
+    Ok(())
+}
+
+/// Creates the config file and its first collection directory with an example
+/// collection, or a `.rbm` project collection in the working directory.
+/// Existing files are left as they are.
+pub fn init(config_file: &Path, project: bool) -> Result<(), ExecutorError> {
+    if project {
+        let working_directory = std::env::current_dir()
+            .map_err(|e| ConfigError::Io(PathBuf::from("."), e.to_string()))?;
+        write_example(&working_directory.join(PROJECT_DIRECTORY))?;
+        return Ok(());
+    }
+    let existed = config_file.exists();
+    let config = config::init_config(config_file)?;
+    match existed {
+        true => println!("Keeping {}", config_file.display()),
+        false => println!("Created {}", config_file.display()),
+    }
+    if let Some(directory) = config.collection_directories().first() {
+        write_example(directory)?;
+    }
+    Ok(())
+}
+
+/// Adds an API with a default context and no endpoints yet.
+pub fn new_api(
+    config: &Config,
+    apis: &HashMap<String, APIConfig>,
+    name: &str,
+    file: &Option<PathBuf>,
+) -> Result<(), ExecutorError> {
+    let path = match file {
+        Some(file) => file.clone(),
+        None => config
+            .collection_directories()
+            .first()
+            .ok_or(ExecutorError::NoCollectionDirectory)?
+            .join(format!("{}.yml", name)),
+    };
+    let mut document = YamlDocument::open(&path)?;
+    if apis.contains_key(name) || document.contains(&[name]) {
+        return Err(ExecutorError::APIAlreadyExists(name.to_string()));
+    }
+    let snippet = format!(
+        "{}:\n  context:\n    default:\n      url: http://localhost:8080\n  endpoints: {{}}\n",
+        scalar(name)
+    );
+    document.insert(&[], &snippet)?;
+    document.save()?;
+    println!("Added API {} to {}", name, document.path().display());
+    Ok(())
+}
+
+pub fn new_endpoint(
+    api: &str,
+    api_config: &APIConfig,
+    name: &str,
+    method: &APIMethod,
+    url: &str,
+    body: &Option<String>,
+) -> Result<(), ExecutorError> {
+    let (mut document, key) = YamlDocument::open_api(api, api_config, &["endpoints"])?;
+    let key = key.as_str();
+    if api_config.contains_endpoint(name) || document.contains(&[key, "endpoints", name]) {
+        return Err(ExecutorError::EndpointAlreadyExists(
+            api.to_string(),
+            name.to_string(),
+        ));
+    }
+    let method = serde_yaml::to_string(method)
+        .map_err(|e| ConfigError::Edit(document.path().to_path_buf(), e.to_string()))?;
+    let mut snippet = format!(
+        "{}:\n  method: {}\n  url: {}\n",
+        scalar(name),
+        method.trim_end(),
+        scalar(url)
+    );
+    match body {
+        Some(body) if body.contains('\n') => {
+            snippet.push_str("  body: |\n");
+            for line in body.lines() {
+                snippet.push_str(&format!("    {}\n", line));
+            }
+        }
+        Some(body) => snippet.push_str(&format!("  body: {}\n", scalar(body))),
+        None => (),
+    }
+    document.insert(&[key, "endpoints"], &snippet)?;
+    document.save()?;
+    println!(
+        "Added endpoint {}.{} to {}",
+        api,
+        name,
+        document.path().display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "./scaffold_test.rs"]
+mod scaffold_test;
@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod tests {
+    use crate::cmd::config::{read_config_or_create_default, APIMethod};
+    use crate::cmd::error::{ConfigError, ExecutorError};
+    use crate::cmd::scaffold::init;
+
+    #[test]
+    fn init_creates_a_loadable_config_test() {
+        let directory = std::env::temp_dir().join(format!("rbm-init-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        let config_file = directory.join("rbm").join("config");
+        init(&config_file, false).unwrap();
+        assert!(directory.join("rbm/apis/example.yml").is_file());
+        // A second init keeps the files as they are.
+        std::fs::write(
+            directory.join("rbm/apis/example.yml"),
+            "other: {endpoints: {}}\n",
+        )
+        .unwrap();
+        init(&config_file, false).unwrap();
+        assert!(
+            std::fs::read_to_string(directory.join("rbm/apis/example.yml"))
+                .unwrap()
+                .starts_with("other")
+        );
+        std::fs::remove_file(directory.join("rbm/apis/example.yml")).unwrap();
+        init(&config_file, false).unwrap();
+
+        let config = read_config_or_create_default(&Some(config_file)).unwrap();
+        let (apis, errors) = config.read_apis();
+        assert!(errors.is_empty(), "{:?}", errors);
+        let example = &apis["example"];
+        assert!(example.contains_context("default"));
+        let post = example.get_api_endpoint("post").unwrap();
+        assert!(matches!(post.method, APIMethod::POST));
+        assert_eq!(post.url, "{{url}}/post");
+        assert_eq!(post.headers.as_ref().unwrap()["Accept"], "application/json");
+        assert!(post.inputs.as_ref().unwrap().contains_key("name"));
+
+        // The config directory cannot be created under a file.
+        let blocked = init(&directory.join("rbm/config/config"), false);
+        assert!(matches!(
+            blocked,
+            Err(ExecutorError::Config(ConfigError::Write(path, _))) if path.ends_with("rbm/config")
+        ));
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,159 @@
+use std::{
+    io::{BufRead, BufReader, Read},
+    time::Duration,
+};
+
+use serde::Serialize;
+
+use super::{
+    error::ExecutorError,
+    stream::{self, Ended, Limits},
+};
+
+/// Delay before reconnecting until the server sends a `retry` field.
+const DEFAULT_RETRY_MS: u64 = 3000;
+const DEFAULT_EVENT: &str = "message";
+
+/// A Server-Sent Event as dispatched to the client.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Event {
+    pub event: String,
+    /// The last event ID seen on the stream, which may come from an earlier event.
+    pub id: Option<String>,
+    pub data: String,
+}
+
+/// Turns the lines of an event stream into events, keeping the last event ID
+/// and the reconnection delay across connections.
+#[derive(Debug)]
+pub struct Parser {
+    event: Option<String>,
+    data: Option<String>,
+    last_id: Option<String>,
+    retry: Duration,
+}
+
+impl Parser {
+    pub fn new() -> Parser {
+        Parser {
+            event: None,
+            data: None,
+            last_id: None,
+            retry: Duration::from_millis(DEFAULT_RETRY_MS),
+        }
+    }
+
+    pub fn last_id(&self) -> Option<&str> {
+        self.last_id.as_deref()
+    }
+
+    /// Drops the event being read, as a connection that ends mid event does.
+    fn restart(&mut self) {
+        self.event = None;
+        self.data = None;
+    }
+
+    /// Reads one line without its line break, an empty line dispatches the
+    /// event read so far unless it has no data.
+    pub fn feed(&mut self, line: &str) -> Option<Event> {
+        if line.is_empty() {
+            let event = self.event.take();
+            return self.data.take().map(|data| Event {
+                event: event.unwrap_or_else(|| DEFAULT_EVENT.to_string()),
+                id: self.last_id.clone(),
+                data,
+            });
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "event" => self.event = Some(value.to_string()),
+            "data" => match &mut self.data {
+                Some(data) => {
+                    data.push('\n');
+                    data.push_str(value);
+                }
+                None => self.data = Some(value.to_string()),
+            },
+            "id" if !value.contains('\0') => {
+                self.last_id = Some(value.to_string()).filter(|id| !id.is_empty());
+            }
+            "retry" if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) => {
+                if let Ok(retry) = value.parse() {
+                    self.retry = Duration::from_millis(retry);
+                }
+            }
+            _ => (),
+        }
+        None
+    }
+}
+
+/// The lines of a body, split on `\n` with a trailing `\r` removed.
+fn lines<R: Read + Send + 'static>(
+    body: R,
+) -> impl Iterator<Item = std::io::Result<String>> + Send {
+    BufReader::new(body).split(b'\n').map(|line| {
+        line.map(|mut line| {
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            String::from_utf8_lossy(&line).into_owned()
+        })
+    })
+}
+
+/// Hands every event of the stream to `on_event` as it arrives. When the
+/// connection ends, waits for the retry delay and calls `reconnect` with the
+/// last event ID, which returns `None` once the server closes for good with a 204.
+/// A reconnection failing to reach the server is retried after the same delay.
+pub fn listen<R, C, E>(
+    first: R,
+    mut reconnect: C,
+    mut on_event: E,
+    limits: &Limits,
+) -> Result<(), ExecutorError>
+where
+    R: Read + Send + 'static,
+    C: FnMut(Option<&str>) -> Result<Option<R>, ExecutorError>,
+    E: FnMut(&Event) -> Result<(), ExecutorError>,
+{
+    let mut parser = Parser::new();
+    let mut received: u64 = 0;
+    let mut body = Some(first);
+    loop {
+        if let Some(connection) = body.take() {
+            parser.restart();
+            let consume = |line: String| {
+                if let Some(event) = parser.feed(&line) {
+                    on_event(&event)?;
+                    received += 1;
+                }
+                Ok(limits.max_events.map(|max| received < max).unwrap_or(true))
+            };
+            match stream::forward(lines(connection), consume, limits.deadline) {
+                Ok(Ended::Finished) | Err(ExecutorError::FailedToReadBody(_)) => (),
+                Ok(Ended::Stopped | Ended::TimedOut) => return Ok(()),
+                Err(e) => return Err(e),
+            }
+        }
+        if stream::wait(parser.retry, limits.deadline)? == Ended::TimedOut {
+            return Ok(());
+        }
+        body = match reconnect(parser.last_id()) {
+            Ok(Some(connection)) => Some(connection),
+            Ok(None) => return Ok(()),
+            Err(ExecutorError::HTTPRequestError(_)) => None,
+            Err(e) => return Err(e),
+        };
+    }
+}
+
+#[cfg(test)]
+#[path = "./sse_test.rs"]
+mod sse_test;
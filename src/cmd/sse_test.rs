@@ -0,0 +1,160 @@
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Cursor, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use crate::cmd::error::ExecutorError;
+    use crate::cmd::sse::{listen, Event, Parser};
+    use crate::cmd::stream::Limits;
+
+    fn event(event: &str, id: Option<&str>, data: &str) -> Event {
+        Event {
+            event: event.to_string(),
+            id: id.map(|id| id.to_string()),
+            data: data.to_string(),
+        }
+    }
+
+    #[test]
+    fn parse_events_test() {
+        let mut parser = Parser::new();
+        let stream =
+            ": comment\nevent: update\nid: 7\ndata: first\ndata:second\n\nretry: 10\n\ndata\n\n";
+        let events: Vec<Event> = stream
+            .split('\n')
+            .filter_map(|line| parser.feed(line))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                event("update", Some("7"), "first\nsecond"),
+                event("message", Some("7"), "")
+            ]
+        );
+        assert_eq!(parser.last_id(), Some("7"));
+    }
+
+    /// Serves each body in turn on its own connection, recording the
+    /// `Last-Event-ID` of every request, then answers 204.
+    fn serve(bodies: Vec<&'static str>) -> (u16, thread::JoinHandle<Vec<Option<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = thread::spawn(move || {
+            let mut last_ids = Vec::new();
+            for index in 0..=bodies.len() {
+                let (mut connection, _) = listener.accept().unwrap();
+                let mut last_id = None;
+                let mut reader = BufReader::new(connection.try_clone().unwrap());
+                reader.read_line(&mut String::new()).unwrap();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    match line.trim_end().split_once(": ") {
+                        Some((key, value)) if key.eq_ignore_ascii_case("last-event-id") => {
+                            last_id = Some(value.to_string())
+                        }
+                        Some(_) => (),
+                        None => break,
+                    }
+                }
+                last_ids.push(last_id);
+                let response = match bodies.get(index) {
+                    Some(body) => format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\n{}",
+                        body
+                    ),
+                    None => "HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n".to_string(),
+                };
+                connection.write_all(response.as_bytes()).unwrap();
+            }
+            last_ids
+        });
+        (port, handle)
+    }
+
+    #[test]
+    fn listen_reconnects_with_last_event_id_test() {
+        let (port, server) = serve(vec!["retry: 10\nid: 1\ndata: a\n\n", "data: b\r\n\r\n"]);
+        let url = format!("http://127.0.0.1:{}/events", port);
+        let client = reqwest::blocking::Client::new();
+        let connect = |last_id: Option<&str>| {
+            let mut request = client.get(&url);
+            if let Some(id) = last_id {
+                request = request.header("Last-Event-ID", id);
+            }
+            request.send().unwrap()
+        };
+        let first = connect(None);
+        let mut events = Vec::new();
+        let limits = Limits {
+            max_events: None,
+            deadline: None,
+        };
+        listen(
+            first,
+            |last_id| {
+                let response = connect(last_id);
+                Ok(Some(response).filter(|r| r.status() != 204))
+            },
+            |e| {
+                events.push(e.data.clone());
+                Ok(())
+            },
+            &limits,
+        )
+        .unwrap();
+        assert_eq!(events, vec!["a", "b"]);
+        assert_eq!(
+            server.join().unwrap(),
+            vec![None, Some("1".to_string()), Some("1".to_string())]
+        );
+    }
+
+    #[test]
+    fn listen_retries_failed_reconnections_test() {
+        let bodies = ["data: b\n\n"];
+        let mut attempts = 0;
+        let mut events = Vec::new();
+        let limits = Limits {
+            max_events: None,
+            deadline: None,
+        };
+        listen(
+            Cursor::new("retry: 10\ndata: a\n\n".as_bytes()),
+            |_| {
+                attempts += 1;
+                match attempts {
+                    1 => Err(ExecutorError::HTTPRequestError(
+                        "connection refused".to_string(),
+                    )),
+                    n => Ok(bodies.get(n - 2).map(|body| Cursor::new(body.as_bytes()))),
+                }
+            },
+            |e| {
+                events.push(e.data.clone());
+                Ok(())
+            },
+            &limits,
+        )
+        .unwrap();
+        assert_eq!(events, vec!["a", "b"]);
+        assert_eq!(attempts, 3);
+
+        let result = listen(
+            Cursor::new("retry: 10\n\n".as_bytes()),
+            |_| {
+                Err(ExecutorError::UnexpectedStatus(
+                    500,
+                    "a 2xx status".to_string(),
+                ))
+            },
+            |_| Ok(()),
+            &limits,
+        );
+        assert!(matches!(
+            result,
+            Err(ExecutorError::UnexpectedStatus(500, _))
+        ));
+    }
+}
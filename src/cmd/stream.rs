@@ -0,0 +1,154 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, RecvTimeoutError},
+        OnceLock,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::error::ExecutorError;
+
+/// How often a stream checks for Ctrl-C and its deadline while no data arrives.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+static HANDLER: OnceLock<Result<(), String>> = OnceLock::new();
+
+/// Why a stream stopped being forwarded.
+#[derive(Debug, PartialEq)]
+pub enum Ended {
+    /// The source has nothing more to give.
+    Finished,
+    /// The consumer asked to stop.
+    Stopped,
+    /// The deadline passed.
+    TimedOut,
+}
+
+/// When to stop reading a stream of events or messages.
+pub struct Limits {
+    pub max_events: Option<u64>,
+    pub deadline: Option<Instant>,
+}
+
+impl Limits {
+    pub fn timed_out(&self) -> bool {
+        self.deadline.map(|d| Instant::now() >= d).unwrap_or(false)
+    }
+}
+
+/// Catches Ctrl-C from now on, the handler can only be installed once per
+/// process so later calls reuse it.
+pub fn watch_interrupt() -> Result<(), ExecutorError> {
+    HANDLER
+        .get_or_init(|| {
+            ctrlc::set_handler(|| INTERRUPTED.store(true, Ordering::SeqCst))
+                .map_err(|e| e.to_string())
+        })
+        .clone()
+        .map_err(ExecutorError::FailedToWatchInterrupt)
+}
+
+pub fn check_interrupt() -> Result<(), ExecutorError> {
+    match INTERRUPTED.load(Ordering::SeqCst) {
+        true => Err(ExecutorError::Interrupted),
+        false => Ok(()),
+    }
+}
+
+/// Waits like `thread::sleep` but returns early on Ctrl-C or at the deadline.
+pub fn wait(duration: Duration, deadline: Option<Instant>) -> Result<Ended, ExecutorError> {
+    watch_interrupt()?;
+    let until = Instant::now() + duration;
+    loop {
+        check_interrupt()?;
+        let now = Instant::now();
+        if deadline.map(|d| now >= d).unwrap_or(false) {
+            return Ok(Ended::TimedOut);
+        }
+        if now >= until {
+            return Ok(Ended::Finished);
+        }
+        thread::sleep(POLL_INTERVAL.min(until - now));
+    }
+}
+
+/// Hands every item of a blocking source to `consume` as soon as it is
+/// produced, until the source ends, `consume` returns false, the deadline
+/// passes or Ctrl-C is pressed. The source runs on its own thread so that a
+/// read waiting for data does not hold up the stop.
+pub fn forward<T, S, C>(
+    source: S,
+    mut consume: C,
+    deadline: Option<Instant>,
+) -> Result<Ended, ExecutorError>
+where
+    T: Send + 'static,
+    S: Iterator<Item = std::io::Result<T>> + Send + 'static,
+    C: FnMut(T) -> Result<bool, ExecutorError>,
+{
+    watch_interrupt()?;
+    let (sender, receiver) = mpsc::sync_channel::<std::io::Result<T>>(16);
+    thread::spawn(move || {
+        for item in source {
+            let failed = item.is_err();
+            if sender.send(item).is_err() || failed {
+                return;
+            }
+        }
+    });
+    loop {
+        check_interrupt()?;
+        let timeout = match deadline {
+            Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                Some(left) => left.min(POLL_INTERVAL),
+                None => return Ok(Ended::TimedOut),
+            },
+            None => POLL_INTERVAL,
+        };
+        match receiver.recv_timeout(timeout) {
+            Ok(Ok(item)) => {
+                if !consume(item)? {
+                    return Ok(Ended::Stopped);
+                }
+            }
+            Ok(Err(e)) => return Err(ExecutorError::FailedToReadBody(e.to_string())),
+            Err(RecvTimeoutError::Timeout) => (),
+            Err(RecvTimeoutError::Disconnected) => return Ok(Ended::Finished),
+        }
+    }
+}
+
+/// Reads a source in chunks of up to `size` bytes.
+pub struct Chunks<R> {
+    reader: R,
+    size: usize,
+}
+
+impl<R: std::io::Read> Chunks<R> {
+    pub fn new(reader: R, size: usize) -> Chunks<R> {
+        Chunks { reader, size }
+    }
+}
+
+impl<R: std::io::Read> Iterator for Chunks<R> {
+    type Item = std::io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut buffer = vec![0; self.size];
+        match self.reader.read(&mut buffer) {
+            Ok(0) => None,
+            Ok(read) => {
+                buffer.truncate(read);
+                Some(Ok(buffer))
+            }
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "./stream_test.rs"]
+mod stream_test;
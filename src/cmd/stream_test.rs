@@ -0,0 +1,119 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use crate::cmd::config::{APIConfig, APIEndpoint};
+    use crate::cmd::executor::Engine;
+    use crate::cmd::stream::{forward, wait, Chunks, Ended};
+
+    /// Answers with a chunked body, sending `first` at once and `second` only
+    /// once released.
+    fn serve() -> (u16, mpsc::Sender<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (release, released) = mpsc::channel();
+        thread::spawn(move || {
+            let (mut connection, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(connection.try_clone().unwrap());
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            let head =
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nTransfer-Encoding: chunked\r\n\r\n";
+            connection
+                .write_all(format!("{}5\r\nfirst\r\n", head).as_bytes())
+                .unwrap();
+            if released.recv().is_ok() {
+                connection.write_all(b"6\r\nsecond\r\n0\r\n\r\n").unwrap();
+            }
+        });
+        (port, release)
+    }
+
+    fn api_config(port: u16) -> APIConfig {
+        let endpoint = APIEndpoint {
+            url: format!("http://127.0.0.1:{}/logs", port),
+            ..Default::default()
+        };
+        APIConfig::new(None, HashMap::from([("logs".to_string(), endpoint)]))
+    }
+
+    #[test]
+    fn forward_chunks_as_they_arrive_test() {
+        let (port, release) = serve();
+        let (_, stream) = Engine::new()
+            .stream("test", &api_config(port), "logs", &None, &[])
+            .unwrap();
+        let mut received = Vec::new();
+        let consume = |chunk: Vec<u8>| {
+            received.push(String::from_utf8(chunk).unwrap());
+            // The rest of the body is only sent once the first chunk is here.
+            let _ = release.send(());
+            Ok(true)
+        };
+        let deadline = Some(Instant::now() + Duration::from_secs(10));
+        assert_eq!(
+            forward(Chunks::new(stream, 1024), consume, deadline).unwrap(),
+            Ended::Finished
+        );
+        assert_eq!(received[0], "first");
+        assert_eq!(received.concat(), "firstsecond");
+    }
+
+    #[test]
+    fn forward_stops_at_the_deadline_test() {
+        let (port, release) = serve();
+        let (_, stream) = Engine::new()
+            .stream("test", &api_config(port), "logs", &None, &[])
+            .unwrap();
+        let mut received = Vec::new();
+        let consume = |chunk: Vec<u8>| {
+            received.push(chunk);
+            Ok(true)
+        };
+        let started = Instant::now();
+        let deadline = Some(started + Duration::from_millis(300));
+        assert_eq!(
+            forward(Chunks::new(stream, 1024), consume, deadline).unwrap(),
+            Ended::TimedOut
+        );
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(received, [b"first"]);
+        drop(release);
+    }
+
+    #[test]
+    fn forward_stops_when_asked_test() {
+        let source = [b"a", b"b", b"c"]
+            .into_iter()
+            .map(|chunk| Ok(chunk.to_vec()));
+        let mut received = Vec::new();
+        let consume = |chunk: Vec<u8>| {
+            received.push(chunk);
+            Ok(received.len() < 2)
+        };
+        assert_eq!(forward(source, consume, None).unwrap(), Ended::Stopped);
+        assert_eq!(received, [b"a", b"b"]);
+    }
+
+    #[test]
+    fn wait_test() {
+        assert_eq!(
+            wait(Duration::from_millis(50), None).unwrap(),
+            Ended::Finished
+        );
+        let started = Instant::now();
+        let deadline = Some(started + Duration::from_millis(200));
+        assert_eq!(
+            wait(Duration::from_secs(10), deadline).unwrap(),
+            Ended::TimedOut
+        );
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
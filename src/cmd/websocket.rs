@@ -0,0 +1,288 @@
+use std::{
+    io::{BufRead, ErrorKind},
+    net::{TcpStream, ToSocketAddrs},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use native_tls::TlsConnector;
+use serde::Serialize;
+use tungstenite::{
+    handshake::{client::Request, HandshakeError},
+    protocol::frame::coding::CloseCode,
+    stream::MaybeTlsStream,
+    Connector,
+};
+
+use super::{
+    config::APIClient,
+    error::ExecutorError,
+    executor::{serialize_body, Header},
+    stream::{self, Limits},
+};
+
+/// How long a read waits for a message before checking for Ctrl-C, the
+/// deadline and the lines to send.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// How long to wait for the server to acknowledge our close.
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageKind {
+    Text,
+    Binary,
+}
+
+/// A message received on a WebSocket, printed as
+/// `{kind, data: {encoding, content}}` by `--output`.
+#[derive(Serialize, Debug)]
+pub struct Message {
+    pub kind: MessageKind,
+    #[serde(serialize_with = "serialize_body")]
+    pub data: Vec<u8>,
+}
+
+enum Received {
+    Message(Message),
+    Nothing,
+    Closed,
+}
+
+/// An open WebSocket along with the handshake response.
+pub struct WebSocket {
+    pub status: u16,
+    pub version: String,
+    pub headers: Vec<Header>,
+    socket: tungstenite::WebSocket<MaybeTlsStream<TcpStream>>,
+}
+
+/// Connects to the first address of the host that answers within the timeout.
+fn connect_tcp(host: &str, port: u16, timeout: Option<Duration>) -> std::io::Result<TcpStream> {
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return TcpStream::connect((host, port)),
+    };
+    let mut last_error = None;
+    for address in (host, port).to_socket_addrs()? {
+        match TcpStream::connect_timeout(&address, timeout) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| {
+        std::io::Error::new(ErrorKind::NotFound, format!("no address for {}", host))
+    }))
+}
+
+fn read_error(e: tungstenite::Error) -> ExecutorError {
+    ExecutorError::FailedToReadBody(e.to_string())
+}
+
+impl WebSocket {
+    /// Opens the connection and runs the handshake, `client.timeout` bounding
+    /// both and `client.insecure` accepting invalid TLS certificates.
+    pub fn connect(
+        request: Request,
+        client: Option<&APIClient>,
+    ) -> Result<WebSocket, ExecutorError> {
+        let request_error = |e: String| ExecutorError::HTTPRequestError(e);
+        let timeout = client.and_then(|c| c.timeout).map(Duration::from_secs);
+        let uri = request.uri();
+        let host = uri
+            .host()
+            .ok_or_else(|| request_error(format!("no host in {}", uri)))?;
+        let port = uri.port_u16().unwrap_or(match uri.scheme_str() {
+            Some("wss") => 443,
+            _ => 80,
+        });
+        let stream = connect_tcp(host, port, timeout).map_err(|e| request_error(e.to_string()))?;
+        stream
+            .set_read_timeout(timeout)
+            .and_then(|_| stream.set_write_timeout(timeout))
+            .map_err(|e| request_error(e.to_string()))?;
+        let connector = match client.and_then(|c| c.insecure) {
+            Some(true) => Some(Connector::NativeTls(
+                TlsConnector::builder()
+                    .danger_accept_invalid_certs(true)
+                    .build()
+                    .map_err(|e| request_error(e.to_string()))?,
+            )),
+            _ => None,
+        };
+        let (socket, response) =
+            tungstenite::client_tls_with_config(request, stream, None, connector).map_err(|e| {
+                match e {
+                    HandshakeError::Failure(tungstenite::Error::Http(response)) => {
+                        ExecutorError::UnexpectedStatus(
+                            response.status().as_u16(),
+                            "101".to_string(),
+                        )
+                    }
+                    e => request_error(e.to_string()),
+                }
+            })?;
+        let timeout = match socket.get_ref() {
+            MaybeTlsStream::Plain(stream) => stream.set_read_timeout(Some(POLL_INTERVAL)),
+            MaybeTlsStream::NativeTls(stream) => {
+                stream.get_ref().set_read_timeout(Some(POLL_INTERVAL))
+            }
+            _ => Ok(()),
+        };
+        timeout.map_err(|e| request_error(e.to_string()))?;
+        let headers = response
+            .headers()
+            .iter()
+            .map(|(key, value)| Header {
+                key: key.to_string(),
+                value: String::from_utf8_lossy(value.as_bytes()).into_owned(),
+            })
+            .collect();
+        Ok(WebSocket {
+            status: response.status().as_u16(),
+            version: format!("{:?}", response.version()),
+            headers,
+            socket,
+        })
+    }
+
+    /// Sends UTF-8 as a text message and anything else as a binary one.
+    pub fn send(&mut self, data: Vec<u8>) -> Result<(), ExecutorError> {
+        let message = match String::from_utf8(data) {
+            Ok(text) => tungstenite::Message::Text(text),
+            Err(e) => tungstenite::Message::Binary(e.into_bytes()),
+        };
+        self.socket
+            .send(message)
+            .map_err(|e| ExecutorError::HTTPRequestError(e.to_string()))
+    }
+
+    fn read(&mut self) -> Result<Received, ExecutorError> {
+        match self.socket.read() {
+            Ok(tungstenite::Message::Text(text)) => Ok(Received::Message(Message {
+                kind: MessageKind::Text,
+                data: text.into_bytes(),
+            })),
+            Ok(tungstenite::Message::Binary(data)) => Ok(Received::Message(Message {
+                kind: MessageKind::Binary,
+                data,
+            })),
+            Ok(tungstenite::Message::Close(Some(frame))) if frame.code != CloseCode::Normal => {
+                eprintln!("Closed by the server: {} {}", frame.code, frame.reason);
+                Ok(Received::Nothing)
+            }
+            Ok(_) => Ok(Received::Nothing),
+            Err(tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed) => {
+                Ok(Received::Closed)
+            }
+            Err(tungstenite::Error::Io(e))
+                if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) =>
+            {
+                Ok(Received::Nothing)
+            }
+            Err(e) => Err(read_error(e)),
+        }
+    }
+
+    /// Starts the closing handshake, the server answers with its own close.
+    fn start_close(&mut self) -> Result<(), ExecutorError> {
+        match self.socket.close(None) {
+            Ok(())
+            | Err(tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed) => {
+                Ok(())
+            }
+            Err(e) => Err(read_error(e)),
+        }
+    }
+
+    /// Closes and waits a little for the server to agree, the messages still
+    /// in flight are dropped.
+    fn close(&mut self) -> Result<(), ExecutorError> {
+        self.start_close()?;
+        let until = Instant::now() + CLOSE_TIMEOUT;
+        while Instant::now() < until {
+            if let Received::Closed = self.read()? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Hands every message to `on_message` until the server closes, a limit is
+    /// reached or Ctrl-C is pressed. Lines from `input` are sent as they come,
+    /// the socket is closed once `input` ends, keeping the replies still on
+    /// their way.
+    pub fn listen<M>(
+        &mut self,
+        input: Option<Receiver<String>>,
+        mut on_message: M,
+        limits: &Limits,
+    ) -> Result<(), ExecutorError>
+    where
+        M: FnMut(&Message) -> Result<(), ExecutorError>,
+    {
+        stream::watch_interrupt()?;
+        let mut input = input;
+        let mut received: u64 = 0;
+        let mut closing: Option<Instant> = None;
+        loop {
+            if let Err(e) = stream::check_interrupt() {
+                self.close()?;
+                return Err(e);
+            }
+            if closing
+                .map(|until| Instant::now() >= until)
+                .unwrap_or(false)
+            {
+                return Ok(());
+            }
+            if limits.timed_out() {
+                return self.close();
+            }
+            if let Some(lines) = &input {
+                match lines.try_recv() {
+                    Ok(line) => self.send(line.into_bytes())?,
+                    Err(TryRecvError::Empty) => (),
+                    Err(TryRecvError::Disconnected) => {
+                        input = None;
+                        closing = Some(Instant::now() + CLOSE_TIMEOUT);
+                        self.start_close()?;
+                    }
+                }
+            }
+            match self.read()? {
+                Received::Message(message) => {
+                    on_message(&message)?;
+                    received += 1;
+                    if limits
+                        .max_events
+                        .map(|max| received >= max)
+                        .unwrap_or(false)
+                    {
+                        return self.close();
+                    }
+                }
+                Received::Nothing => (),
+                Received::Closed => return Ok(()),
+            }
+        }
+    }
+}
+
+/// The lines typed on stdin, read on their own thread.
+pub fn stdin_lines() -> Receiver<String> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in std::io::stdin().lock().lines().map_while(Result::ok) {
+            if sender.send(line).is_err() {
+                return;
+            }
+        }
+    });
+    receiver
+}
+
+#[cfg(test)]
+#[path = "./websocket_test.rs"]
+mod websocket_test;
@@ -0,0 +1,166 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use tungstenite::handshake::server::{Request, Response};
+
+    use crate::cmd::config::{
+        APIBody, APIBodyType, APIClient, APIConfig, APIContext, APIEndpoint, APIMethod,
+    };
+    use crate::cmd::error::ExecutorError;
+    use crate::cmd::executor::Engine;
+    use crate::cmd::stream::Limits;
+    use crate::cmd::websocket::MessageKind;
+
+    /// Echoes every message back in upper case until the client closes,
+    /// returning the `Authorization` header of the handshake.
+    fn serve() -> (u16, thread::JoinHandle<Option<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = thread::spawn(move || {
+            let (connection, _) = listener.accept().unwrap();
+            let mut authorization = None;
+            // The error type is set by tungstenite.
+            #[allow(clippy::result_large_err)]
+            let callback = |request: &Request, response: Response| {
+                authorization = request
+                    .headers()
+                    .get("Authorization")
+                    .map(|v| v.to_str().unwrap().to_string());
+                Ok(response)
+            };
+            let mut socket = tungstenite::accept_hdr(connection, callback).unwrap();
+            while let Ok(message) = socket.read() {
+                if let tungstenite::Message::Text(text) = message {
+                    socket
+                        .send(tungstenite::Message::Text(text.to_uppercase()))
+                        .unwrap();
+                }
+            }
+            authorization
+        });
+        (port, handle)
+    }
+
+    #[test]
+    fn websocket_sends_body_and_lines_test() {
+        let (port, server) = serve();
+        let endpoint = APIEndpoint {
+            method: APIMethod::WS,
+            url: "ws://{{url}}/chat".to_string(),
+            headers: Some(HashMap::from([(
+                "Authorization".to_string(),
+                "{{auth}}".to_string(),
+            )])),
+            body: Some(APIBody::new("{{greeting}}", APIBodyType::STRING)),
+            ..Default::default()
+        };
+        let context: APIContext = HashMap::from([
+            ("url".to_string(), format!("127.0.0.1:{}", port)),
+            ("auth".to_string(), "Bearer token".to_string()),
+            ("greeting".to_string(), "hello".to_string()),
+        ]);
+        let api_config = APIConfig::new(
+            Some(HashMap::from([("local".to_string(), context)])),
+            HashMap::from([("chat".to_string(), endpoint)]),
+        );
+        let mut engine = Engine::new();
+        let (mut socket, first_message) = engine
+            .connect("test", &api_config, "chat", &Some("local".to_string()), &[])
+            .unwrap();
+        assert_eq!(socket.status, 101);
+        assert_eq!(first_message.as_deref(), Some("hello".as_bytes()));
+        socket.send(first_message.unwrap()).unwrap();
+
+        let (sender, lines) = mpsc::channel();
+        sender.send("bye".to_string()).unwrap();
+        drop(sender);
+        let mut received = Vec::new();
+        let limits = Limits {
+            max_events: None,
+            deadline: None,
+        };
+        socket
+            .listen(
+                Some(lines),
+                |message| {
+                    assert_eq!(message.kind, MessageKind::Text);
+                    received.push(String::from_utf8(message.data.clone()).unwrap());
+                    Ok(())
+                },
+                &limits,
+            )
+            .unwrap();
+        assert_eq!(received, vec!["HELLO", "BYE"]);
+        assert_eq!(server.join().unwrap().as_deref(), Some("Bearer token"));
+    }
+
+    #[test]
+    fn websocket_client_timeout_test() {
+        // Accepts the connection but never answers the handshake.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || listener.accept().unwrap());
+        let endpoint = APIEndpoint {
+            method: APIMethod::WS,
+            url: format!("ws://127.0.0.1:{}/chat", port),
+            client: Some(APIClient {
+                timeout: Some(1),
+                insecure: Some(true),
+                follow_redirects: None,
+            }),
+            ..Default::default()
+        };
+        let api_config = APIConfig::new(None, HashMap::from([("chat".to_string(), endpoint)]));
+        let started = Instant::now();
+        let result = Engine::new().connect("test", &api_config, "chat", &None, &[]);
+        assert!(matches!(result, Err(ExecutorError::HTTPRequestError(_))));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        drop(server.join().unwrap());
+    }
+
+    #[test]
+    fn websocket_file_body_test() {
+        let (port, server) = serve();
+        let directory = std::env::temp_dir().join(format!("rbm-websocket-{}", port));
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("subscribe.json");
+        std::fs::write(&path, r#"{"subscribe": "prices"}"#).unwrap();
+        let endpoint = |path: &std::path::Path| APIEndpoint {
+            method: APIMethod::WS,
+            url: format!("ws://127.0.0.1:{}/chat", port),
+            body: Some(APIBody::new(&path.display().to_string(), APIBodyType::FILE)),
+            ..Default::default()
+        };
+        let api_config = APIConfig::new(
+            None,
+            HashMap::from([
+                ("chat".to_string(), endpoint(&path)),
+                (
+                    "missing".to_string(),
+                    endpoint(&directory.join("missing.json")),
+                ),
+            ]),
+        );
+        let mut engine = Engine::new();
+        let result = engine.connect("test", &api_config, "missing", &None, &[]);
+        assert!(matches!(
+            result,
+            Err(ExecutorError::FailedToOpenBodyFile(_, _))
+        ));
+        let (socket, first_message) = engine
+            .connect("test", &api_config, "chat", &None, &[])
+            .unwrap();
+        assert_eq!(
+            first_message.as_deref(),
+            Some(r#"{"subscribe": "prices"}"#.as_bytes())
+        );
+        drop(socket);
+        server.join().unwrap();
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
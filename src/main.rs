@@ -4,9 +4,8 @@ use cmd::parser::parse_cli_args;
 
 fn main() {
     let cli_args = parse_cli_args();
-    let config = cli_args.read_config();
-    let result = cmd::execute(&cli_args, &config);
-    if let Err(e) = result {
-        eprintln!("{}", e)
+    if let Err(e) = cmd::execute(&cli_args) {
+        eprintln!("{}", e);
+        std::process::exit(e.exit_code())
     }
 }